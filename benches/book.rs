@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+
+use bookbuilder::bookmanager::{BookManager, Order, OrderManager};
+use bookbuilder::generator::{EventMix, Generator, GeneratorConfig};
+use bookbuilder::messages::Body;
+
+fn orders(symbols: usize, mix: EventMix) -> Vec<Order> {
+    let config = GeneratorConfig {
+        symbols: (0..symbols).map(|index| format!("SYM{}", index)).collect(),
+        message_count: 50000,
+        mix,
+        ..GeneratorConfig::default()
+    };
+    let mut listing = HashMap::new();
+    Generator::new(config)
+        .filter(|message| !matches!(message.body, Body::StockDirectory(_)))
+        .map(|message| Order::new(&message, &mut listing))
+        .collect()
+}
+
+fn process(c: &mut Criterion) {
+    // Quoting flow dominated by adds and cancels, as seen on a real feed.
+    let quoting = EventMix {
+        add: 48,
+        execute: 2,
+        cancel: 10,
+        replace: 10,
+        delete: 30,
+    };
+    let cases = [
+        ("default/1", orders(1, EventMix::default())),
+        ("default/50", orders(50, EventMix::default())),
+        ("quoting/1", orders(1, quoting)),
+        ("quoting/50", orders(50, quoting)),
+    ];
+
+    let mut group = c.benchmark_group("order_manager");
+    group.sample_size(20);
+    for (name, orders) in cases.iter() {
+        group.throughput(Throughput::Elements(orders.len() as u64));
+        group.bench_function(*name, |b| {
+            b.iter_batched(
+                || (OrderManager::new(), BookManager::new()),
+                |(mut order_manager, mut book_manager)| {
+                    for order in orders.iter() {
+                        order_manager.process(order, &mut book_manager);
+                    }
+                    (order_manager, book_manager)
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, process);
+criterion_main!(benches);
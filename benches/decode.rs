@@ -0,0 +1,335 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+use bookbuilder::enums::*;
+use bookbuilder::generator::{Generator, GeneratorConfig};
+use bookbuilder::messages::*;
+use bookbuilder::price::{Price4, Price8};
+use bookbuilder::timestamp::Timestamp;
+
+fn stock() -> Stock {
+    Stock::from("AAPL")
+}
+
+fn samples() -> Vec<(&'static str, Body)> {
+    vec![
+        (
+            "A",
+            Body::AddOrder(AddOrder {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: Timestamp(34200000000000),
+                reference: 1,
+                side: Side::Buy,
+                shares: 100,
+                stock: stock(),
+                price: Price4(1000000),
+                attribution: None,
+            }),
+        ),
+        (
+            "F",
+            Body::AddOrder(AddOrder {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: Timestamp(34200000000000),
+                reference: 1,
+                side: Side::Sell,
+                shares: 100,
+                stock: stock(),
+                price: Price4(1000000),
+                attribution: Some(Mpid::from("NITE")),
+            }),
+        ),
+        (
+            "E",
+            Body::ExecutedOrder(ExecutedOrder {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: Timestamp(34200000000000),
+                reference: 1,
+                executed_shares: 100,
+                match_number: 1,
+            }),
+        ),
+        (
+            "C",
+            Body::ExecutedWithPriceOrder(ExecutedWithPriceOrder {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: Timestamp(34200000000000),
+                reference: 1,
+                executed_shares: 100,
+                match_number: 1,
+                printable: true,
+                price: Price4(1000000),
+            }),
+        ),
+        (
+            "X",
+            Body::CancelOrder(CancelOrder {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: Timestamp(34200000000000),
+                reference: 1,
+                canceled_shares: 100,
+            }),
+        ),
+        (
+            "D",
+            Body::DeleteOrder(DeleteOrder {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: Timestamp(34200000000000),
+                reference: 1,
+            }),
+        ),
+        (
+            "U",
+            Body::ReplaceOrder(ReplaceOrder {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: Timestamp(34200000000000),
+                original_reference: 1,
+                new_reference: 2,
+                price: Price4(1000000),
+                shares: 100,
+            }),
+        ),
+        (
+            "R",
+            Body::StockDirectory(StockDirectory {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: Timestamp(34200000000000),
+                stock: stock(),
+                market_category: MarketCategory::NasdaqGlobalSelect,
+                financial_status: FinancialStatus::Normal,
+                round_lot_size: 100,
+                round_lots_only: false,
+                issue_classification: IssueClassification::CommonStock,
+                issue_subtype: IssueSubType::CommonShares,
+                authenticity: Authenticity::Production,
+                short_sale_threshold: ThresholdIndicator::NotRestricted,
+                ipo_flag: IpoFlag::NotNew,
+                luld_ref_price_tier: LuldRefPriceTier::Tier1,
+                etp_flag: EtpFlag::IsNot,
+                etp_leverage_factor: 0,
+                inverse_indicator: false,
+            }),
+        ),
+        (
+            "H",
+            Body::StockTradingAction(StockTradingAction {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: Timestamp(34200000000000),
+                stock: stock(),
+                trading_state: TradingState::Trading,
+                reason: Alpha::from(""),
+            }),
+        ),
+        (
+            "Y",
+            Body::RegShoRestriction(RegShoRestriction {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: Timestamp(34200000000000),
+                stock: stock(),
+                reg_sho_action: RegShoAction::NoPriceTest,
+            }),
+        ),
+        (
+            "L",
+            Body::MarketParticipantPosition(MarketParticipantPosition {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: Timestamp(34200000000000),
+                mpid: Mpid::from("NITE"),
+                stock: stock(),
+                primary_market_maker: true,
+                market_maker_mode: MarketMakerMode::Normal,
+                market_participant_state: MarketParticipantState::Active,
+            }),
+        ),
+        (
+            "V",
+            Body::MwcbDeclineLevel(MwcbDeclineLevel {
+                stock_locate: 0,
+                tracking_number: 0,
+                timestamp: Timestamp(34200000000000),
+                level1: Price8(400000000000),
+                level2: Price8(350000000000),
+                level3: Price8(300000000000),
+            }),
+        ),
+        (
+            "W",
+            Body::MwcbStatus(MwcbStatus {
+                stock_locate: 0,
+                tracking_number: 0,
+                timestamp: Timestamp(34200000000000),
+                breached_level: MwcbLevel::Level1,
+            }),
+        ),
+        (
+            "K",
+            Body::IpoQuotingPeriodUpdate(IpoQuotingPeriodUpdate {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: Timestamp(34200000000000),
+                stock: stock(),
+                release_time: Timestamp::from_seconds(36000),
+                release_qualifier: IpoReleaseQualifier::Anticipated,
+                ipo_price: Price4(200000),
+            }),
+        ),
+        (
+            "J",
+            Body::LuldAuctionCollar(LuldAuctionCollar {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: Timestamp(34200000000000),
+                stock: stock(),
+                reference_price: Price4(1000000),
+                upper_price: Price4(1050000),
+                lower_price: Price4(950000),
+                extension: 0,
+            }),
+        ),
+        (
+            "h",
+            Body::OperationalHalt(OperationalHalt {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: Timestamp(34200000000000),
+                stock: stock(),
+                market_code: MarketCode::Nasdaq,
+                action: OperationalHaltAction::Halted,
+            }),
+        ),
+        (
+            "O",
+            Body::DirectListingPriceDiscovery(DirectListingPriceDiscovery {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: Timestamp(34200000000000),
+                stock: stock(),
+                open_eligible: true,
+                min_allowable_price: Price4(900000),
+                max_allowable_price: Price4(1100000),
+                near_execution_price: Price4(1000000),
+                near_execution_time: Timestamp(34200000000000),
+                lower_price_range_collar: Price4(950000),
+                upper_price_range_collar: Price4(1050000),
+            }),
+        ),
+        (
+            "P",
+            Body::NonCrossTrade(NonCrossTrade {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: Timestamp(34200000000000),
+                reference: 0,
+                side: Side::Buy,
+                shares: 100,
+                stock: stock(),
+                price: Price4(1000000),
+                match_number: 1,
+            }),
+        ),
+        (
+            "Q",
+            Body::CrossTrade(CrossTrade {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: Timestamp(34200000000000),
+                shares: 10000,
+                stock: stock(),
+                cross_price: Price4(1000000),
+                match_number: 1,
+                cross_type: CrossType::Opening,
+            }),
+        ),
+        (
+            "B",
+            Body::BrokenTrade(BrokenTrade {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: Timestamp(34200000000000),
+                match_number: 1,
+            }),
+        ),
+        (
+            "I",
+            Body::NetOrderImbalance(NetOrderImbalance {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: Timestamp(34200000000000),
+                paired_shares: 10000,
+                imbalance_shares: 500,
+                imbalance_direction: ImbalanceDirection::Buy,
+                stock: stock(),
+                far_price: Price4(1010000),
+                near_price: Price4(1005000),
+                current_reference_price: Price4(1000000),
+                cross_type: CrossType::Opening,
+                price_variation_indicator: PriceVariationIndicator::LessThan1Percent,
+            }),
+        ),
+        (
+            "N",
+            Body::RetailPriceImprovement(RetailPriceImprovement {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: Timestamp(34200000000000),
+                stock: stock(),
+                interest: RetailInterest::Buy,
+            }),
+        ),
+        (
+            "S",
+            Body::SystemEvent(SystemEvent {
+                stock_locate: 0,
+                tracking_number: 0,
+                timestamp: Timestamp(34200000000000),
+                event_code: SystemEventCode::StartOfMarketHours,
+            }),
+        ),
+    ]
+}
+
+fn decode_message_types(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode");
+    group.throughput(Throughput::Elements(1));
+    for (msg_type, body) in samples() {
+        let bytes = Message::with_body(body).encode();
+        group.bench_function(msg_type, |b| b.iter(|| Message::new(black_box(&bytes))));
+    }
+    group.finish();
+}
+
+fn decode_feed(c: &mut Criterion) {
+    let feed: Vec<Vec<u8>> = Generator::new(GeneratorConfig {
+        symbols: vec![String::from("AAPL"), String::from("MSFT")],
+        message_count: 100000,
+        ..GeneratorConfig::default()
+    })
+    .map(|message| message.encode())
+    .collect();
+
+    let mut group = c.benchmark_group("decode");
+    group.throughput(Throughput::Elements(feed.len() as u64));
+    group.bench_function("feed", |b| {
+        b.iter(|| {
+            for bytes in feed.iter() {
+                black_box(Message::new(bytes).unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, decode_message_types, decode_feed);
+criterion_main!(benches);
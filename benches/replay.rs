@@ -0,0 +1,120 @@
+use std::env;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::Path;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+use bookbuilder::generator::{Generator, GeneratorConfig};
+use bookbuilder::messages::{Message, MessageView};
+use bookbuilder::parallel::replay_sharded;
+use bookbuilder::pipeline::Pipeline;
+use bookbuilder::reader::{self, Reader, StopCondition};
+use bookbuilder::writer::{write_itch, write_pcap};
+
+const MESSAGE_COUNT: u64 = 200000;
+
+fn config() -> GeneratorConfig {
+    GeneratorConfig {
+        symbols: ["AAPL", "MSFT", "AMZN", "NVDA", "TSLA"]
+            .iter()
+            .map(|symbol| symbol.to_string())
+            .collect(),
+        message_count: MESSAGE_COUNT,
+        ..GeneratorConfig::default()
+    }
+}
+
+fn walk(mut reader: Box<dyn Reader>) -> usize {
+    let mut count = 0;
+    while let Some(packet) = reader.next_packet() {
+        count += packet.messages().count();
+    }
+    count
+}
+
+fn replay(mut reader: Box<dyn Reader>) -> Pipeline {
+    let mut pipeline = Pipeline::new();
+    while let Some(packet) = reader.next_packet() {
+        for bytes in packet.messages() {
+            pipeline.process(&Message::new(bytes).unwrap());
+        }
+    }
+    pipeline
+}
+
+fn replay_symbol(path: &Path, symbol: &str) -> Pipeline {
+    let mut pipeline = Pipeline::with_symbols(&[symbol]);
+    let mut reader = reader::open(path).unwrap();
+    while let Some(packet) = reader.next_packet() {
+        for bytes in packet.messages() {
+            pipeline
+                .process_view(&MessageView::new(bytes).unwrap())
+                .unwrap();
+        }
+    }
+    pipeline
+}
+
+fn end_to_end(c: &mut Criterion) {
+    let dir = env::temp_dir().join(format!("bookbuilder-bench-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let pcap = dir.join("replay.pcap");
+    let itch = dir.join("replay.itch");
+    write_pcap(
+        BufWriter::new(File::create(&pcap).unwrap()),
+        Generator::new(config()),
+        10,
+        config().trade_date,
+    )
+    .unwrap();
+    write_itch(
+        BufWriter::new(File::create(&itch).unwrap()),
+        Generator::new(config()),
+    )
+    .unwrap();
+
+    let mut group = c.benchmark_group("read");
+    group.throughput(Throughput::Elements(MESSAGE_COUNT));
+    group.bench_function("pcap/mmap", |b| {
+        b.iter(|| walk(reader::open(&pcap).unwrap()))
+    });
+    group.bench_function("pcap/libpcap", |b| {
+        b.iter(|| walk(reader::open_libpcap(&pcap).unwrap()))
+    });
+    group.bench_function("itch/mmap", |b| {
+        b.iter(|| walk(reader::open(&itch).unwrap()))
+    });
+    group.bench_function("itch/buffered", |b| {
+        b.iter(|| walk(reader::open_libpcap(&itch).unwrap()))
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("replay");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(MESSAGE_COUNT));
+    group.bench_function("pcap", |b| b.iter(|| replay(reader::open(&pcap).unwrap())));
+    group.bench_function("pcap/libpcap", |b| {
+        b.iter(|| replay(reader::open_libpcap(&pcap).unwrap()))
+    });
+    group.bench_function("itch", |b| b.iter(|| replay(reader::open(&itch).unwrap())));
+    group.bench_function("itch/buffered", |b| {
+        b.iter(|| replay(reader::open_libpcap(&itch).unwrap()))
+    });
+    group.bench_function("pcap/filtered", |b| b.iter(|| replay_symbol(&pcap, "AAPL")));
+    group.bench_function("pcap/sharded", |b| {
+        b.iter(|| {
+            replay_sharded(
+                reader::open(&pcap).unwrap().as_mut(),
+                StopCondition::default(),
+                4,
+            )
+        })
+    });
+    group.finish();
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+criterion_group!(benches, end_to_end);
+criterion_main!(benches);
@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use bookbuilder::messages::Message;
+use bookbuilder::reader::{MmapReader, Reader};
+
+fuzz_target!(|data: &[u8]| {
+    let mut reader = MmapReader::from_bytes(data);
+    while let Some(packet) = reader.next_packet() {
+        for bytes in packet.messages() {
+            let _ = Message::new(bytes);
+        }
+    }
+});
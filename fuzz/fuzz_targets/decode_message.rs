@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use bookbuilder::messages::Message;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(message) = Message::new(data) {
+        let _ = message.encode();
+    }
+});
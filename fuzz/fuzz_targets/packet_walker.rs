@@ -0,0 +1,21 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use bookbuilder::messages::{Message, PacketHeader};
+use bookbuilder::reader::Packet;
+use bookbuilder::utils::PACKET_HEADER_LENGTH;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(header) = PacketHeader::new(data) {
+        let packet = Packet {
+            sequence_number: header.sequence_number,
+            message_count: header.message_count,
+            timestamp: None,
+            data: &data[PACKET_HEADER_LENGTH..],
+        };
+        for bytes in packet.messages() {
+            let _ = Message::new(bytes);
+        }
+    }
+});
@@ -0,0 +1,35 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use bookbuilder::messages::Message;
+use bookbuilder::pipeline::Pipeline;
+use bookbuilder::reader::{ItchReader, Reader};
+
+fuzz_target!(|data: &[u8]| {
+    let mut reader = ItchReader::from_reader(data);
+    let mut pipeline = Pipeline::new();
+
+    while let Some(packet) = reader.next_packet() {
+        for bytes in packet.messages() {
+            if let Ok(message) = Message::new(bytes) {
+                pipeline.process(&message);
+            }
+        }
+    }
+
+    let stock_locates: Vec<u16> = pipeline.listing.keys().copied().collect();
+    for stock_locate in stock_locates {
+        let reference_price = pipeline
+            .trade_manager
+            .trades(stock_locate)
+            .last()
+            .map(|trade| trade.price);
+        let _ = pipeline
+            .book_manager
+            .indicative_auction(stock_locate, reference_price);
+        let _ = pipeline
+            .auction_manager
+            .latest_imbalance(stock_locate);
+    }
+});
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::enums::{CrossType, ImbalanceDirection, PriceVariationIndicator};
+use crate::messages::{Body, Message};
+use crate::price::Price4;
+use crate::timestamp::Timestamp;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Imbalance {
+    pub timestamp: Timestamp,
+    pub paired_shares: u64,
+    pub imbalance_shares: u64,
+    pub imbalance_direction: ImbalanceDirection,
+    pub far_price: Price4,
+    pub near_price: Price4,
+    pub current_reference_price: Price4,
+    pub price_variation_indicator: PriceVariationIndicator,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Cross {
+    pub timestamp: Timestamp,
+    pub shares: u64,
+    pub cross_price: Price4,
+    pub match_number: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Auction {
+    pub cross_type: CrossType,
+    pub imbalances: Vec<Imbalance>,
+    pub cross: Option<Cross>,
+}
+
+#[derive(Debug)]
+pub struct AuctionManager {
+    auctions_per_stock_locate: HashMap<u16, Vec<Auction>>,
+}
+
+impl Default for AuctionManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AuctionManager {
+    pub fn new() -> Self {
+        AuctionManager {
+            auctions_per_stock_locate: HashMap::new(),
+        }
+    }
+
+    pub fn merge(&mut self, other: AuctionManager) {
+        self.auctions_per_stock_locate
+            .extend(other.auctions_per_stock_locate);
+    }
+
+    fn auction(&mut self, stock_locate: u16, cross_type: CrossType) -> &mut Auction {
+        let auctions = self
+            .auctions_per_stock_locate
+            .entry(stock_locate)
+            .or_default();
+        // A new auction of the same type starts once the previous one crossed.
+        match auctions
+            .iter()
+            .rposition(|a| a.cross_type == cross_type && a.cross.is_none())
+        {
+            Some(index) => &mut auctions[index],
+            None => {
+                auctions.push(Auction {
+                    cross_type,
+                    imbalances: Vec::new(),
+                    cross: None,
+                });
+                auctions.last_mut().unwrap()
+            }
+        }
+    }
+
+    pub fn auctions(&self, stock_locate: u16) -> &[Auction] {
+        self.auctions_per_stock_locate
+            .get(&stock_locate)
+            .map_or(&[], |auctions| auctions.as_slice())
+    }
+
+    pub fn latest_imbalance(&self, stock_locate: u16) -> Option<(CrossType, &Imbalance)> {
+        self.auctions_per_stock_locate
+            .get(&stock_locate)?
+            .iter()
+            .filter_map(|auction| Some((auction.cross_type, auction.imbalances.last()?)))
+            .max_by_key(|(_, imbalance)| imbalance.timestamp)
+    }
+
+    pub fn process(&mut self, message: &Message) {
+        match &message.body {
+            Body::NetOrderImbalance(noii) => self
+                .auction(noii.stock_locate, noii.cross_type)
+                .imbalances
+                .push(Imbalance {
+                    timestamp: noii.timestamp,
+                    paired_shares: noii.paired_shares,
+                    imbalance_shares: noii.imbalance_shares,
+                    imbalance_direction: noii.imbalance_direction,
+                    far_price: noii.far_price,
+                    near_price: noii.near_price,
+                    current_reference_price: noii.current_reference_price,
+                    price_variation_indicator: noii.price_variation_indicator,
+                }),
+            Body::CrossTrade(cross) => {
+                self.auction(cross.stock_locate, cross.cross_type).cross = Some(Cross {
+                    timestamp: cross.timestamp,
+                    shares: cross.shares,
+                    cross_price: cross.cross_price,
+                    match_number: cross.match_number,
+                })
+            }
+            _ => (),
+        }
+    }
+
+    pub fn display_auctions(&self, stock_locate: u16) {
+        for auction in self.auctions(stock_locate) {
+            println!("----- {:?} Cross -----", auction.cross_type);
+            for imbalance in auction.imbalances.iter() {
+                println!(
+                    "[{}] paired: {} imbalance: {} {:?} far: {} near: {} ref: {} variation: {:?}",
+                    imbalance.timestamp,
+                    imbalance.paired_shares,
+                    imbalance.imbalance_shares,
+                    imbalance.imbalance_direction,
+                    imbalance.far_price,
+                    imbalance.near_price,
+                    imbalance.current_reference_price,
+                    imbalance.price_variation_indicator
+                );
+            }
+            if let Some(cross) = auction.cross {
+                println!(
+                    "[{}] cross: {} @ {} match: {}",
+                    cross.timestamp, cross.shares, cross.cross_price, cross.match_number
+                );
+            }
+        }
+    }
+
+    pub fn display_latest_imbalance(&self, stock_locate: u16) {
+        if let Some((cross_type, imbalance)) = self.latest_imbalance(stock_locate) {
+            println!(
+                "NOII ({:?}) [{}] paired: {} imbalance: {} {:?} far: {} near: {} ref: {}",
+                cross_type,
+                imbalance.timestamp,
+                imbalance.paired_shares,
+                imbalance.imbalance_shares,
+                imbalance.imbalance_direction,
+                imbalance.far_price,
+                imbalance.near_price,
+                imbalance.current_reference_price
+            );
+        }
+    }
+}
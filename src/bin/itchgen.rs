@@ -0,0 +1,169 @@
+use clap::{value_parser, Arg, Command};
+use std::fs::File;
+use std::io::BufWriter;
+
+use bookbuilder::generator::{Generator, GeneratorConfig, PriceProcess};
+use bookbuilder::timestamp::TradeDate;
+use bookbuilder::writer::{write_itch, write_pcap};
+
+fn main() {
+    let matches = Command::new("itchgen")
+        .about("Generates a synthetic ITCH 5.0 feed.")
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("FILE")
+                .help("Sets the output file path.")
+                .num_args(1)
+                .required(true),
+        )
+        .arg(
+            Arg::new("format")
+                .short('F')
+                .long("format")
+                .value_name("FORMAT")
+                .help("Writes a pcap capture or a raw ITCH file.")
+                .value_parser(["pcap", "itch"])
+                .num_args(1)
+                .default_value("pcap"),
+        )
+        .arg(
+            Arg::new("symbols")
+                .short('s')
+                .long("symbols")
+                .value_name("SYMBOLS")
+                .help("Sets the comma separated list of generated symbols.")
+                .value_delimiter(',')
+                .num_args(1)
+                .default_value("AAPL"),
+        )
+        .arg(
+            Arg::new("messages")
+                .short('m')
+                .long("messages")
+                .value_name("COUNT")
+                .help("Sets the number of order events to generate.")
+                .value_parser(value_parser!(u64))
+                .num_args(1)
+                .default_value("10000"),
+        )
+        .arg(
+            Arg::new("rate")
+                .short('r')
+                .long("rate")
+                .value_name("RATE")
+                .help("Sets the average number of events per second.")
+                .value_parser(value_parser!(u64).range(1..))
+                .num_args(1)
+                .default_value("100000"),
+        )
+        .arg(
+            Arg::new("price")
+                .short('p')
+                .long("price")
+                .value_name("PRICE")
+                .help("Sets the initial mid price in 1/10000 dollars.")
+                .value_parser(value_parser!(u32))
+                .num_args(1)
+                .default_value("1000000"),
+        )
+        .arg(
+            Arg::new("tick")
+                .long("tick")
+                .value_name("TICK")
+                .help("Sets the tick size in 1/10000 dollars.")
+                .value_parser(value_parser!(u32).range(1..))
+                .num_args(1)
+                .default_value("100"),
+        )
+        .arg(
+            Arg::new("volatility")
+                .short('v')
+                .long("volatility")
+                .value_name("TICKS")
+                .help("Sets the maximum mid price move in ticks per event.")
+                .value_parser(value_parser!(u32))
+                .num_args(1)
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("process")
+                .long("process")
+                .value_name("PROCESS")
+                .help("Sets the mid price process: a random walk, a walk reverting to the initial price or a walk with occasional jumps of up to 20 ticks.")
+                .value_parser(["random-walk", "mean-reverting", "jump"])
+                .num_args(1)
+                .default_value("random-walk"),
+        )
+        .arg(
+            Arg::new("date")
+                .long("date")
+                .value_name("DATE")
+                .help("Sets the trade date of pcap capture timestamps as YYYY-MM-DD.")
+                .value_parser(|value: &str| value.parse::<TradeDate>())
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("seed")
+                .long("seed")
+                .value_name("SEED")
+                .help("Sets the random seed.")
+                .value_parser(value_parser!(u64))
+                .num_args(1)
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("batch")
+                .short('b')
+                .long("batch")
+                .value_name("COUNT")
+                .help("Sets the maximum number of messages per MoldUDP64 packet, packets are also cut to fit a UDP datagram.")
+                .value_parser(value_parser!(u16).range(1..))
+                .num_args(1)
+                .default_value("10"),
+        )
+        .get_matches();
+
+    let config = GeneratorConfig {
+        symbols: matches
+            .get_many::<String>("symbols")
+            .unwrap()
+            .cloned()
+            .collect(),
+        message_count: *matches.get_one::<u64>("messages").unwrap(),
+        rate: *matches.get_one::<u64>("rate").unwrap(),
+        initial_price: *matches.get_one::<u32>("price").unwrap(),
+        tick_size: *matches.get_one::<u32>("tick").unwrap(),
+        volatility: *matches.get_one::<u32>("volatility").unwrap(),
+        seed: *matches.get_one::<u64>("seed").unwrap(),
+        price_process: match matches.get_one::<String>("process").unwrap().as_str() {
+            "mean-reverting" => PriceProcess::MeanReverting { strength: 0.05 },
+            "jump" => PriceProcess::Jump {
+                probability: 0.01,
+                size: 20,
+            },
+            _ => PriceProcess::RandomWalk,
+        },
+        trade_date: matches
+            .get_one::<TradeDate>("date")
+            .copied()
+            .unwrap_or(GeneratorConfig::default().trade_date),
+        ..GeneratorConfig::default()
+    };
+    let trade_date = config.trade_date;
+    let output = File::create(matches.get_one::<String>("output").unwrap()).unwrap();
+    let writer = BufWriter::new(output);
+    let generator = Generator::new(config);
+
+    match matches.get_one::<String>("format").unwrap().as_str() {
+        "itch" => write_itch(writer, generator),
+        _ => write_pcap(
+            writer,
+            generator,
+            *matches.get_one::<u16>("batch").unwrap() as usize,
+            trade_date,
+        ),
+    }
+    .unwrap();
+}
@@ -1,99 +1,170 @@
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
-use crate::enums::{OrderType, Side};
-use crate::messages::{Body, Message};
+use serde::Serialize;
 
-type Books = HashMap<u16, (HashMap<u32, u32>, HashMap<u32, u32>)>;
+use crate::enums::{LevelAction, OrderType, Side};
+use crate::error::DecodeError;
+use crate::messages::{Body, Message, MessageView, Mpid, Stock};
+use crate::price::Price4;
+
+type Books = HashMap<u16, (BTreeMap<Price4, Level>, BTreeMap<Price4, Level>)>;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Level {
+    pub shares: u32,
+    pub orders: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PriceLevel {
+    pub price: Price4,
+    pub shares: u32,
+    pub orders: u32,
+}
+
+// Best bid and offer of a book, an empty side has no price and no shares.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Bbo {
+    pub bid_price: Option<Price4>,
+    pub bid_shares: u32,
+    pub ask_price: Option<Price4>,
+    pub ask_shares: u32,
+}
+
+// Aggregate state of a level after an update, no shares nor orders once it
+// is deleted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LevelDelta {
+    pub side: Side,
+    pub price: Price4,
+    pub shares: u32,
+    pub orders: u32,
+    pub action: LevelAction,
+}
 
 #[derive(Debug, Clone, Copy)]
 pub struct Order {
     msg_type: OrderType,
     reference: u64,
+    new_reference: u64,
     side: Side,
     quantity: u32,
     pub stock_locate: u16,
-    price: u32,
+    price: Price4,
+    attribution: Option<Mpid>,
 }
 
 impl Order {
-    pub fn new(message: &Message, listing: &mut HashMap<u16, String>) -> Self {
+    pub fn new(message: &Message, listing: &mut HashMap<u16, Stock>) -> Self {
         match &message.body {
             Body::AddOrder(add) => {
-                listing
-                    .entry(add.stock_locate)
-                    .or_insert_with(|| add.stock.clone());
+                listing.entry(add.stock_locate).or_insert(add.stock);
                 Order {
                     msg_type: OrderType::Add,
                     reference: add.reference,
+                    new_reference: 0, /* unused */
                     side: add.side,
                     quantity: add.shares,
                     stock_locate: add.stock_locate,
                     price: add.price,
+                    attribution: add.attribution,
                 }
             }
             Body::ExecutedWithPriceOrder(exec) => {
                 Order {
                     msg_type: OrderType::ExecutedWithPrice,
                     reference: exec.reference,
-                    side: Side::Buy, /* unused */
+                    new_reference: 0, /* unused */
+                    side: Side::Buy,  /* unused */
                     quantity: exec.executed_shares,
                     stock_locate: exec.stock_locate,
                     price: exec.price,
+                    attribution: None, /* unused */
                 }
             }
             Body::DeleteOrder(del) => {
                 Order {
                     msg_type: OrderType::Delete,
                     reference: del.reference,
-                    side: Side::Buy, /* unused */
-                    quantity: 0,     /* unused */
+                    new_reference: 0, /* unused */
+                    side: Side::Buy,  /* unused */
+                    quantity: 0,      /* unused */
                     stock_locate: del.stock_locate,
-                    price: 0, /* unused */
+                    price: Price4(0),  /* unused */
+                    attribution: None, /* unused */
                 }
             }
             Body::ExecutedOrder(exec) => {
                 Order {
                     msg_type: OrderType::Executed,
                     reference: exec.reference,
-                    side: Side::Buy, /* unused */
+                    new_reference: 0, /* unused */
+                    side: Side::Buy,  /* unused */
                     quantity: exec.executed_shares,
                     stock_locate: exec.stock_locate,
-                    price: 0, /* unused */
+                    price: Price4(0),  /* unused */
+                    attribution: None, /* unused */
                 }
             }
             Body::ReplaceOrder(repl) => {
                 Order {
                     msg_type: OrderType::Replace,
                     reference: repl.original_reference,
+                    new_reference: repl.new_reference,
                     side: Side::Buy, /* unused */
                     quantity: repl.shares,
                     stock_locate: repl.stock_locate,
                     price: repl.price,
+                    attribution: None, /* unused */
                 }
             }
             Body::CancelOrder(cancel) => {
                 Order {
                     msg_type: OrderType::Cancel,
                     reference: cancel.reference,
-                    side: Side::Buy, /* unused */
+                    new_reference: 0, /* unused */
+                    side: Side::Buy,  /* unused */
                     quantity: cancel.canceled_shares,
                     stock_locate: cancel.stock_locate,
-                    price: 0, /* unused */
-                }
-            }
-            Body::StockDirectory(_) => {
-                Order {
-                    msg_type: OrderType::Cancel, /* unused */
-                    reference: 0,                /* unused */
-                    side: Side::Buy,             /* unused */
-                    quantity: 0,                 /* unused */
-                    stock_locate: 0,             /* unused */
-                    price: 0,                    /* unused */
+                    price: Price4(0),  /* unused */
+                    attribution: None, /* unused */
                 }
             }
             _ => unreachable!(),
         }
     }
+
+    // Same as new, with the fields read from the view instead of a decoded
+    // message.
+    pub fn from_view(
+        view: &MessageView,
+        listing: &mut HashMap<u16, Stock>,
+    ) -> Result<Self, DecodeError> {
+        let msg_type = match view.msg_type() {
+            b'A' | b'F' => OrderType::Add,
+            b'C' => OrderType::ExecutedWithPrice,
+            b'D' => OrderType::Delete,
+            b'E' => OrderType::Executed,
+            b'U' => OrderType::Replace,
+            b'X' => OrderType::Cancel,
+            _ => unreachable!(),
+        };
+        let side = view.side()?.unwrap_or(Side::Buy);
+        if let Some(stock) = view.stock() {
+            listing.entry(view.stock_locate()).or_insert(stock);
+        }
+        Ok(Order {
+            msg_type,
+            reference: view.reference().unwrap_or_default(),
+            new_reference: view.new_reference().unwrap_or_default(),
+            side,
+            quantity: view.shares().unwrap_or_default(),
+            stock_locate: view.stock_locate(),
+            price: view.price().unwrap_or_default(),
+            attribution: view.attribution(),
+        })
+    }
 }
 
 #[derive(Debug)]
@@ -101,6 +172,12 @@ pub struct OrderManager {
     orders_per_stock_locate: HashMap<u16, Vec<Order>>,
 }
 
+impl Default for OrderManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl OrderManager {
     pub fn new() -> Self {
         OrderManager {
@@ -108,6 +185,11 @@ impl OrderManager {
         }
     }
 
+    pub fn merge(&mut self, other: OrderManager) {
+        self.orders_per_stock_locate
+            .extend(other.orders_per_stock_locate);
+    }
+
     pub fn execute_order(
         &mut self,
         stock_locate: u16,
@@ -127,7 +209,14 @@ impl OrderManager {
     ) {
         if let Some(orders) = self.orders_per_stock_locate.get_mut(&stock_locate) {
             if let Some(index) = orders.iter().position(|&o| o.reference == reference) {
-                if orders[index].quantity - canceled_qty == 0 {
+                if orders[index].quantity <= canceled_qty {
+                    book_manager.delete_quantity(
+                        stock_locate,
+                        orders[index].price,
+                        orders[index].quantity,
+                        orders[index].side,
+                        true,
+                    );
                     orders.remove(index);
                 } else {
                     orders[index].quantity -= canceled_qty;
@@ -136,6 +225,7 @@ impl OrderManager {
                         orders[index].price,
                         canceled_qty,
                         orders[index].side,
+                        false,
                     );
                 }
             }
@@ -166,6 +256,7 @@ impl OrderManager {
                     orders[index].price,
                     orders[index].quantity,
                     orders[index].side,
+                    true,
                 );
                 orders.remove(index);
             }
@@ -182,12 +273,35 @@ impl OrderManager {
         if let Some(orders) = self.orders_per_stock_locate.get_mut(&stock_locate) {
             if let Some(index) = orders.iter().position(|o| old_ref == o.reference) {
                 new_order.side = orders[index].side;
+                new_order.attribution = orders[index].attribution;
                 self.delete_order(stock_locate, old_ref, book_manager);
                 self.add_order(new_order, book_manager);
             }
         }
     }
 
+    pub fn order_price(&self, stock_locate: u16, reference: u64) -> Option<Price4> {
+        self.orders_per_stock_locate
+            .get(&stock_locate)
+            .and_then(|orders| orders.iter().find(|o| o.reference == reference))
+            .map(|o| o.price)
+    }
+
+    pub fn attributions(&self, stock_locate: u16, side: Side) -> HashMap<Price4, Vec<Mpid>> {
+        let mut attributions = HashMap::<Price4, Vec<Mpid>>::new();
+        if let Some(orders) = self.orders_per_stock_locate.get(&stock_locate) {
+            for order in orders.iter().filter(|o| o.side == side) {
+                if let Some(mpid) = order.attribution {
+                    let mpids = attributions.entry(order.price).or_default();
+                    if !mpids.contains(&mpid) {
+                        mpids.push(mpid);
+                    }
+                }
+            }
+        }
+        attributions
+    }
+
     pub fn process(&mut self, order: &Order, book_manager: &mut BookManager) {
         match &order.msg_type {
             OrderType::Add => self.add_order(order, book_manager),
@@ -215,11 +329,13 @@ impl OrderManager {
             OrderType::Replace => {
                 let mut new_order = Order {
                     msg_type: order.msg_type,
-                    reference: order.reference,
+                    reference: order.new_reference,
+                    new_reference: 0, /* unused */
                     side: order.side,
                     quantity: order.quantity,
                     stock_locate: order.stock_locate,
                     price: order.price,
+                    attribution: order.attribution,
                 };
                 self.modify_order(
                     order.stock_locate,
@@ -232,91 +348,330 @@ impl OrderManager {
     }
 }
 
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct IndicativeAuction {
+    pub price: Price4,
+    pub matched_shares: u64,
+    pub imbalance_shares: u64,
+    pub imbalance_side: Option<Side>,
+}
+
 #[derive(Debug)]
 pub struct BookManager {
     pub books_per_stock_locate: Books,
+    // Top of book tracking, off unless track_bbo is called: locates whose top
+    // was touched since the last bbo_changes call and their last BBO.
+    track_bbo: bool,
+    bbo_touched: Vec<u16>,
+    bbos: HashMap<u16, Bbo>,
+    // Level updates, recorded once track_deltas is called.
+    track_deltas: bool,
+    deltas: Vec<(u16, LevelDelta)>,
+}
+
+impl Default for BookManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl BookManager {
     pub fn new() -> Self {
         BookManager {
             books_per_stock_locate: HashMap::new(),
+            track_bbo: false,
+            bbo_touched: Vec::new(),
+            bbos: HashMap::new(),
+            track_deltas: false,
+            deltas: Vec::new(),
         }
     }
 
-    pub fn add_quantity(&mut self, stock_locate: u16, price: u32, qty: u32, side: Side) {
-        if let Some(book) = self.books_per_stock_locate.get_mut(&stock_locate) {
+    pub fn merge(&mut self, other: BookManager) {
+        self.books_per_stock_locate
+            .extend(other.books_per_stock_locate);
+        self.bbo_touched.extend(other.bbo_touched);
+        self.bbos.extend(other.bbos);
+        self.deltas.extend(other.deltas);
+    }
+
+    pub fn track_bbo(&mut self) {
+        self.track_bbo = true;
+    }
+
+    // Called once the book has been updated at the given price: the top is
+    // touched when the price is at or better than the remaining best price,
+    // which includes a best level that has just been removed.
+    fn touch(&mut self, stock_locate: u16, price: Price4, side: Side) {
+        if !self.track_bbo || self.bbo_touched.contains(&stock_locate) {
+            return;
+        }
+        let touched = match (self.books_per_stock_locate.get(&stock_locate), side) {
+            (Some(book), Side::Buy) => book
+                .0
+                .last_key_value()
+                .is_none_or(|(best, _)| price >= *best),
+            (Some(book), Side::Sell) => book
+                .1
+                .first_key_value()
+                .is_none_or(|(best, _)| price <= *best),
+            (None, _) => false,
+        };
+        if touched {
+            self.bbo_touched.push(stock_locate);
+        }
+    }
+
+    pub fn bbo(&self, stock_locate: u16) -> Bbo {
+        let mut bbo = Bbo::default();
+        if let Some(book) = self.books_per_stock_locate.get(&stock_locate) {
+            if let Some((price, level)) = book.0.last_key_value() {
+                bbo.bid_price = Some(*price);
+                bbo.bid_shares = level.shares;
+            }
+            if let Some((price, level)) = book.1.first_key_value() {
+                bbo.ask_price = Some(*price);
+                bbo.ask_shares = level.shares;
+            }
+        }
+        bbo
+    }
+
+    pub fn track_deltas(&mut self) {
+        self.track_deltas = true;
+    }
+
+    fn record_delta(&mut self, stock_locate: u16, price: Price4, side: Side, action: LevelAction) {
+        if !self.track_deltas {
+            return;
+        }
+        let level = match (self.books_per_stock_locate.get(&stock_locate), side) {
+            (Some(book), Side::Buy) => book.0.get(&price),
+            (Some(book), Side::Sell) => book.1.get(&price),
+            (None, _) => None,
+        };
+        let level = level.copied().unwrap_or_default();
+        self.deltas.push((
+            stock_locate,
+            LevelDelta {
+                side,
+                price,
+                shares: level.shares,
+                orders: level.orders,
+                action,
+            },
+        ));
+    }
+
+    // Level updates since the previous call, in the order they were applied.
+    pub fn take_deltas(&mut self) -> Vec<(u16, LevelDelta)> {
+        std::mem::take(&mut self.deltas)
+    }
+
+    // BBOs whose price or shares differ from the previous call, by locate.
+    // Called after each message, so that a replace moving an order away and
+    // back to the top yields no change.
+    pub fn bbo_changes(&mut self) -> Vec<(u16, Bbo)> {
+        let mut changes = Vec::new();
+        for stock_locate in std::mem::take(&mut self.bbo_touched) {
+            let bbo = self.bbo(stock_locate);
+            if self.bbos.insert(stock_locate, bbo) != Some(bbo) {
+                changes.push((stock_locate, bbo));
+            }
+        }
+        changes
+    }
+
+    pub fn add_quantity(&mut self, stock_locate: u16, price: Price4, qty: u32, side: Side) {
+        let action = if let Some(book) = self.books_per_stock_locate.get_mut(&stock_locate) {
             match side {
                 Side::Buy => {
                     if let Some(book_buy_entry) = book.0.get_mut(&price) {
-                        *book_buy_entry += qty;
+                        book_buy_entry.shares = book_buy_entry.shares.saturating_add(qty);
+                        book_buy_entry.orders += 1;
+                        LevelAction::Change
                     } else {
-                        book.0.insert(price, qty);
+                        book.0.insert(
+                            price,
+                            Level {
+                                shares: qty,
+                                orders: 1,
+                            },
+                        );
+                        LevelAction::New
                     }
                 }
                 Side::Sell => {
                     if let Some(book_sell_entry) = book.1.get_mut(&price) {
-                        *book_sell_entry += qty;
+                        book_sell_entry.shares = book_sell_entry.shares.saturating_add(qty);
+                        book_sell_entry.orders += 1;
+                        LevelAction::Change
                     } else {
-                        book.1.insert(price, qty);
+                        book.1.insert(
+                            price,
+                            Level {
+                                shares: qty,
+                                orders: 1,
+                            },
+                        );
+                        LevelAction::New
                     }
                 }
             }
         } else {
             self.books_per_stock_locate
-                .insert(stock_locate, (HashMap::new(), HashMap::new()));
-            self.add_quantity(stock_locate, price, qty, side);
-        }
+                .insert(stock_locate, (BTreeMap::new(), BTreeMap::new()));
+            return self.add_quantity(stock_locate, price, qty, side);
+        };
+        self.touch(stock_locate, price, side);
+        self.record_delta(stock_locate, price, side, action);
     }
 
-    pub fn delete_quantity(&mut self, stock_locate: u16, price: u32, qty: u32, side: Side) {
+    pub fn delete_quantity(
+        &mut self,
+        stock_locate: u16,
+        price: Price4,
+        qty: u32,
+        side: Side,
+        order_removed: bool,
+    ) {
         if let Some(book) = self.books_per_stock_locate.get_mut(&stock_locate) {
-            match side {
-                Side::Buy => {
-                    if let Some(book_buy_entry) = book.0.get_mut(&price) {
-                        if *book_buy_entry <= qty {
-                            book.0.remove(&price);
-                        } else {
-                            *book_buy_entry -= qty;
-                        }
+            let action = match side {
+                Side::Buy => match book.0.get_mut(&price) {
+                    Some(book_buy_entry) if book_buy_entry.shares <= qty => {
+                        book.0.remove(&price);
+                        Some(LevelAction::Delete)
                     }
-                }
-                Side::Sell => {
-                    if let Some(book_sell_entry) = book.1.get_mut(&price) {
-                        if *book_sell_entry <= qty {
-                            book.1.remove(&price);
-                        } else {
-                            *book_sell_entry -= qty;
-                        }
+                    Some(book_buy_entry) => {
+                        book_buy_entry.shares -= qty;
+                        book_buy_entry.orders -= order_removed as u32;
+                        Some(LevelAction::Change)
                     }
-                }
+                    None => None,
+                },
+                Side::Sell => match book.1.get_mut(&price) {
+                    Some(book_sell_entry) if book_sell_entry.shares <= qty => {
+                        book.1.remove(&price);
+                        Some(LevelAction::Delete)
+                    }
+                    Some(book_sell_entry) => {
+                        book_sell_entry.shares -= qty;
+                        book_sell_entry.orders -= order_removed as u32;
+                        Some(LevelAction::Change)
+                    }
+                    None => None,
+                },
+            };
+            self.touch(stock_locate, price, side);
+            if let Some(action) = action {
+                self.record_delta(stock_locate, price, side, action);
             }
         }
     }
 
-    pub fn display_book(self, stock_locate: u16, book_depth: usize) {
-        if let Some(book) = self.books_per_stock_locate.get(&stock_locate) {
-            let mut sell_hash_vec: Vec<(&u32, &u32)> = book.1.iter().collect();
-            let mut buy_hash_vec: Vec<(&u32, &u32)> = book.0.iter().collect();
+    // Best price first, as far as the given depth.
+    pub fn levels(&self, stock_locate: u16, side: Side, book_depth: usize) -> Vec<PriceLevel> {
+        let level = |(price, level): (&Price4, &Level)| PriceLevel {
+            price: *price,
+            shares: level.shares,
+            orders: level.orders,
+        };
+        match (self.books_per_stock_locate.get(&stock_locate), side) {
+            (Some(book), Side::Buy) => book.0.iter().rev().take(book_depth).map(level).collect(),
+            (Some(book), Side::Sell) => book.1.iter().take(book_depth).map(level).collect(),
+            (None, _) => Vec::new(),
+        }
+    }
+
+    pub fn indicative_auction(
+        &self,
+        stock_locate: u16,
+        reference_price: Option<Price4>,
+    ) -> Option<IndicativeAuction> {
+        let book = self.books_per_stock_locate.get(&stock_locate)?;
+        let prices: BTreeSet<Price4> = book.0.keys().chain(book.1.keys()).copied().collect();
+        let mut best: Option<IndicativeAuction> = None;
+
+        for price in prices {
+            let buy_shares: u64 = book
+                .0
+                .range(price..)
+                .map(|(_, level)| level.shares as u64)
+                .sum();
+            let sell_shares: u64 = book
+                .1
+                .range(..=price)
+                .map(|(_, level)| level.shares as u64)
+                .sum();
+            let matched_shares = buy_shares.min(sell_shares);
+            if matched_shares == 0 {
+                continue;
+            }
 
-            sell_hash_vec.sort_by(|a, b| b.0.cmp(a.0));
-            buy_hash_vec.sort_by(|a, b| b.0.cmp(a.0));
+            let candidate = IndicativeAuction {
+                price,
+                matched_shares,
+                imbalance_shares: buy_shares.abs_diff(sell_shares),
+                imbalance_side: match buy_shares.cmp(&sell_shares) {
+                    Ordering::Greater => Some(Side::Buy),
+                    Ordering::Less => Some(Side::Sell),
+                    Ordering::Equal => None,
+                },
+            };
 
+            // Nasdaq tie-break: maximize executed shares, then minimize the
+            // imbalance, then stay closest to the reference price.
+            let is_better = match &best {
+                None => true,
+                Some(current) => candidate
+                    .matched_shares
+                    .cmp(&current.matched_shares)
+                    .then(current.imbalance_shares.cmp(&candidate.imbalance_shares))
+                    .then(match reference_price {
+                        Some(reference) => current
+                            .price
+                            .abs_diff(reference)
+                            .cmp(&candidate.price.abs_diff(reference)),
+                        None => Ordering::Equal,
+                    })
+                    .is_gt(),
+            };
+            if is_better {
+                best = Some(candidate);
+            }
+        }
+
+        best
+    }
+
+    pub fn display_book(&self, stock_locate: u16, book_depth: usize) {
+        if let Some(book) = self.books_per_stock_locate.get(&stock_locate) {
             println!("----- Sell -----");
-            for (level, entry) in sell_hash_vec.iter().rev().enumerate() {
-                if level >= book_depth {
-                    break;
-                }
-                println!("[{}] {} @ {:.4}", level, entry.1, *entry.0 as f64 / 10000.0);
+            for (level, entry) in book.1.iter().take(book_depth).enumerate() {
+                println!("[{}] {} @ {}", level, entry.1.shares, entry.0);
             }
 
             println!("----- Buy -----");
-            for (level, entry) in buy_hash_vec.iter().enumerate() {
-                if level >= book_depth {
-                    break;
+            for (level, entry) in book.0.iter().rev().take(book_depth).enumerate() {
+                println!("[{}] {} @ {}", level, entry.1.shares, entry.0);
+            }
+        }
+    }
+
+    pub fn display_indicative_auction(&self, stock_locate: u16, reference_price: Option<Price4>) {
+        println!("----- Indicative Auction -----");
+        match self.indicative_auction(stock_locate, reference_price) {
+            Some(auction) => {
+                print!(
+                    "{} @ {} imbalance: {}",
+                    auction.matched_shares, auction.price, auction.imbalance_shares
+                );
+                match auction.imbalance_side {
+                    Some(side) => println!(" {:?}", side),
+                    None => println!(),
                 }
-                println!("[{}] {} @ {:.4}", level, entry.1, *entry.0 as f64 / 10000.0);
             }
+            None => println!("No cross"),
         }
     }
 }
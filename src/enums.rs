@@ -1,9 +1,91 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use serde::Serialize;
+
+use crate::error::DecodeError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum Side {
     Sell,
     Buy,
 }
 
+impl Side {
+    pub fn new(byte: u8) -> Result<Self, DecodeError> {
+        match byte {
+            b'S' => Ok(Side::Sell),
+            b'B' => Ok(Side::Buy),
+            _ => Err(DecodeError::InvalidValue(byte)),
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            Side::Sell => b'S',
+            Side::Buy => b'B',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SystemEventCode {
+    StartOfMessages,
+    StartOfSystemHours,
+    StartOfMarketHours,
+    EndOfMarketHours,
+    EndOfSystemHours,
+    EndOfMessages,
+}
+
+impl SystemEventCode {
+    pub fn new(byte: u8) -> Result<Self, DecodeError> {
+        match byte {
+            b'O' => Ok(SystemEventCode::StartOfMessages),
+            b'S' => Ok(SystemEventCode::StartOfSystemHours),
+            b'Q' => Ok(SystemEventCode::StartOfMarketHours),
+            b'M' => Ok(SystemEventCode::EndOfMarketHours),
+            b'E' => Ok(SystemEventCode::EndOfSystemHours),
+            b'C' => Ok(SystemEventCode::EndOfMessages),
+            _ => Err(DecodeError::InvalidValue(byte)),
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            SystemEventCode::StartOfMessages => b'O',
+            SystemEventCode::StartOfSystemHours => b'S',
+            SystemEventCode::StartOfMarketHours => b'Q',
+            SystemEventCode::EndOfMarketHours => b'M',
+            SystemEventCode::EndOfSystemHours => b'E',
+            SystemEventCode::EndOfMessages => b'C',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum LevelAction {
+    New,
+    Change,
+    Delete,
+}
+
+impl LevelAction {
+    pub fn new(byte: u8) -> Result<Self, DecodeError> {
+        match byte {
+            b'N' => Ok(LevelAction::New),
+            b'C' => Ok(LevelAction::Change),
+            b'D' => Ok(LevelAction::Delete),
+            _ => Err(DecodeError::InvalidValue(byte)),
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            LevelAction::New => b'N',
+            LevelAction::Change => b'C',
+            LevelAction::Delete => b'D',
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum OrderType {
     Add,
@@ -14,7 +96,7 @@ pub enum OrderType {
     Cancel,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum MarketCategory {
     NasdaqGlobalSelect,
     NasdaqGlobalMarket,
@@ -28,23 +110,37 @@ pub enum MarketCategory {
 }
 
 impl MarketCategory {
-    pub fn new(byte: u8) -> Self {
+    pub fn new(byte: u8) -> Result<Self, DecodeError> {
         match byte {
-            b'Q' => MarketCategory::NasdaqGlobalSelect,
-            b'G' => MarketCategory::NasdaqCapitalMarket,
-            b'S' => MarketCategory::NasdaqGlobalMarket,
-            b'N' => MarketCategory::Nyse,
-            b'A' => MarketCategory::NyseMkt,
-            b'P' => MarketCategory::NyseArca,
-            b'Z' => MarketCategory::BatsZExchange,
-            b'V' => MarketCategory::InvestorsExchange,
-            b' ' => MarketCategory::Unavailable,
-            _ => unreachable!(),
+            b'Q' => Ok(MarketCategory::NasdaqGlobalSelect),
+            b'G' => Ok(MarketCategory::NasdaqGlobalMarket),
+            b'S' => Ok(MarketCategory::NasdaqCapitalMarket),
+            b'N' => Ok(MarketCategory::Nyse),
+            b'A' => Ok(MarketCategory::NyseMkt),
+            b'P' => Ok(MarketCategory::NyseArca),
+            b'Z' => Ok(MarketCategory::BatsZExchange),
+            b'V' => Ok(MarketCategory::InvestorsExchange),
+            b' ' => Ok(MarketCategory::Unavailable),
+            _ => Err(DecodeError::InvalidValue(byte)),
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            MarketCategory::NasdaqGlobalSelect => b'Q',
+            MarketCategory::NasdaqGlobalMarket => b'G',
+            MarketCategory::NasdaqCapitalMarket => b'S',
+            MarketCategory::Nyse => b'N',
+            MarketCategory::NyseMkt => b'A',
+            MarketCategory::NyseArca => b'P',
+            MarketCategory::BatsZExchange => b'Z',
+            MarketCategory::InvestorsExchange => b'V',
+            MarketCategory::Unavailable => b' ',
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum FinancialStatus {
     Normal,
     Deficient,
@@ -60,25 +156,41 @@ pub enum FinancialStatus {
 }
 
 impl FinancialStatus {
-    pub fn new(byte: u8) -> Self {
+    pub fn new(byte: u8) -> Result<Self, DecodeError> {
         match byte {
-            b'D' => FinancialStatus::Normal,
-            b'E' => FinancialStatus::Deficient,
-            b'Q' => FinancialStatus::Delinquent,
-            b'S' => FinancialStatus::Bankrupt,
-            b'G' => FinancialStatus::Suspended,
-            b'H' => FinancialStatus::DeficientBankrupt,
-            b'J' => FinancialStatus::DeficientDelinquent,
-            b'K' => FinancialStatus::DelinquentBankrupt,
-            b'C' => FinancialStatus::DeficientDelinquentBankrupt,
-            b'N' => FinancialStatus::EtpSuspended,
-            b' ' => FinancialStatus::Unavailable,
-            _ => unreachable!(),
+            b'N' => Ok(FinancialStatus::Normal),
+            b'D' => Ok(FinancialStatus::Deficient),
+            b'E' => Ok(FinancialStatus::Delinquent),
+            b'Q' => Ok(FinancialStatus::Bankrupt),
+            b'S' => Ok(FinancialStatus::Suspended),
+            b'G' => Ok(FinancialStatus::DeficientBankrupt),
+            b'H' => Ok(FinancialStatus::DeficientDelinquent),
+            b'J' => Ok(FinancialStatus::DelinquentBankrupt),
+            b'K' => Ok(FinancialStatus::DeficientDelinquentBankrupt),
+            b'C' => Ok(FinancialStatus::EtpSuspended),
+            b' ' => Ok(FinancialStatus::Unavailable),
+            _ => Err(DecodeError::InvalidValue(byte)),
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            FinancialStatus::Normal => b'N',
+            FinancialStatus::Deficient => b'D',
+            FinancialStatus::Delinquent => b'E',
+            FinancialStatus::Bankrupt => b'Q',
+            FinancialStatus::Suspended => b'S',
+            FinancialStatus::DeficientBankrupt => b'G',
+            FinancialStatus::DeficientDelinquent => b'H',
+            FinancialStatus::DelinquentBankrupt => b'J',
+            FinancialStatus::DeficientDelinquentBankrupt => b'K',
+            FinancialStatus::EtpSuspended => b'C',
+            FinancialStatus::Unavailable => b' ',
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum IssueClassification {
     AmericanDepositaryShare,
     Bond,
@@ -99,30 +211,51 @@ pub enum IssueClassification {
 }
 
 impl IssueClassification {
-    pub fn new(byte: u8) -> Self {
+    pub fn new(byte: u8) -> Result<Self, DecodeError> {
         match byte {
-            b'A' => IssueClassification::AmericanDepositaryShare,
-            b'B' => IssueClassification::Bond,
-            b'C' => IssueClassification::CommonStock,
-            b'F' => IssueClassification::DepositoryReceipt,
-            b'I' => IssueClassification::A144,
-            b'L' => IssueClassification::LimitedPartnership,
-            b'N' => IssueClassification::Notes,
-            b'O' => IssueClassification::OrdinaryShare,
-            b'P' => IssueClassification::PreferredStock,
-            b'Q' => IssueClassification::OtherSecurities,
-            b'R' => IssueClassification::Right,
-            b'S' => IssueClassification::SharesOfBeneficialInterest,
-            b'T' => IssueClassification::ConvertibleDebenture,
-            b'U' => IssueClassification::Unit,
-            b'V' => IssueClassification::UnitsPerBenifInt,
-            b'W' => IssueClassification::Warrant,
-            _ => unreachable!(),
-        }
-    }
-}
-
-#[derive(Debug, PartialEq, Eq)]
+            b'A' => Ok(IssueClassification::AmericanDepositaryShare),
+            b'B' => Ok(IssueClassification::Bond),
+            b'C' => Ok(IssueClassification::CommonStock),
+            b'F' => Ok(IssueClassification::DepositoryReceipt),
+            b'I' => Ok(IssueClassification::A144),
+            b'L' => Ok(IssueClassification::LimitedPartnership),
+            b'N' => Ok(IssueClassification::Notes),
+            b'O' => Ok(IssueClassification::OrdinaryShare),
+            b'P' => Ok(IssueClassification::PreferredStock),
+            b'Q' => Ok(IssueClassification::OtherSecurities),
+            b'R' => Ok(IssueClassification::Right),
+            b'S' => Ok(IssueClassification::SharesOfBeneficialInterest),
+            b'T' => Ok(IssueClassification::ConvertibleDebenture),
+            b'U' => Ok(IssueClassification::Unit),
+            b'V' => Ok(IssueClassification::UnitsPerBenifInt),
+            b'W' => Ok(IssueClassification::Warrant),
+            _ => Err(DecodeError::InvalidValue(byte)),
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            IssueClassification::AmericanDepositaryShare => b'A',
+            IssueClassification::Bond => b'B',
+            IssueClassification::CommonStock => b'C',
+            IssueClassification::DepositoryReceipt => b'F',
+            IssueClassification::A144 => b'I',
+            IssueClassification::LimitedPartnership => b'L',
+            IssueClassification::Notes => b'N',
+            IssueClassification::OrdinaryShare => b'O',
+            IssueClassification::PreferredStock => b'P',
+            IssueClassification::OtherSecurities => b'Q',
+            IssueClassification::Right => b'R',
+            IssueClassification::SharesOfBeneficialInterest => b'S',
+            IssueClassification::ConvertibleDebenture => b'T',
+            IssueClassification::Unit => b'U',
+            IssueClassification::UnitsPerBenifInt => b'V',
+            IssueClassification::Warrant => b'W',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum IssueSubType {
     PreferredTrustSecurities,
     AlphaIndexETNs,
@@ -185,101 +318,626 @@ pub enum IssueSubType {
 }
 
 impl IssueSubType {
-    pub fn new(bytes: &[u8]) -> Self {
+    pub fn new(bytes: &[u8]) -> Result<Self, DecodeError> {
         match bytes {
-            b"A " => IssueSubType::PreferredTrustSecurities,
-            b"AI" => IssueSubType::AlphaIndexETNs,
-            b"B " => IssueSubType::IndexBasedDerivative,
-            b"C " => IssueSubType::CommonShares,
-            b"CB" => IssueSubType::CommodityBasedTrustShares,
-            b"CF" => IssueSubType::CommodityFuturesTrustShares,
-            b"CL" => IssueSubType::CommodityLinkedSecurities,
-            b"CM" => IssueSubType::CommodityIndexTrustShares,
-            b"CO" => IssueSubType::CollateralizedMortgageObligation,
-            b"CT" => IssueSubType::CurrencyTrustShares,
-            b"CU" => IssueSubType::CommodityCurrencyLinkedSecurities,
-            b"CW" => IssueSubType::CurrencyWarrants,
-            b"D " => IssueSubType::GlobalDepositaryShares,
-            b"E " => IssueSubType::ETFPortfolioDepositaryReceipt,
-            b"EG" => IssueSubType::EquityGoldShares,
-            b"EI" => IssueSubType::ETNEquityIndexLinkedSecurities,
-            b"EM" => IssueSubType::ExchangeTradedManagedFunds,
-            b"EN" => IssueSubType::ExchangeTradedNotes,
-            b"EU" => IssueSubType::EquityUnits,
-            b"F " => IssueSubType::Holdrs,
-            b"FI" => IssueSubType::ETNFixedIncomeLinkedSecurities,
-            b"FL" => IssueSubType::ETNFuturesLinkedSecurities,
-            b"G " => IssueSubType::GlobalShares,
-            b"I " => IssueSubType::ETFIndexFundShares,
-            b"IR" => IssueSubType::InterestRate,
-            b"IW" => IssueSubType::IndexWarrant,
-            b"IX" => IssueSubType::IndexLinkedExchangeableNotes,
-            b"J " => IssueSubType::CorporateBackedTrustSecurity,
-            b"L " => IssueSubType::ContingentLitigationRight,
-            b"LL" => IssueSubType::Llc,
-            b"M " => IssueSubType::EquityBasedDerivative,
-            b"MF" => IssueSubType::ManagedFundShares,
-            b"ML" => IssueSubType::ETNMultiFactorIndexLinkedSecurities,
-            b"MT" => IssueSubType::ManagedTrustSecurities,
-            b"N " => IssueSubType::NYRegistryShares,
-            b"O " => IssueSubType::OpenEndedMutualFund,
-            b"P " => IssueSubType::PrivatelyHeldSecurity,
-            b"PP" => IssueSubType::PoisonPill,
-            b"PU" => IssueSubType::PartnershipUnits,
-            b"Q " => IssueSubType::ClosedEndFunds,
-            b"R " => IssueSubType::RegS,
-            b"RC" => IssueSubType::CommodityRedeemableCommodityLinkedSecurities,
-            b"RF" => IssueSubType::ETNRedeemableFuturesLinkedSecurities,
-            b"RT" => IssueSubType::Reit,
-            b"RU" => IssueSubType::CommodityRedeemableCurrencyLinkedSecurities,
-            b"S " => IssueSubType::Seed,
-            b"SC" => IssueSubType::SpotRateClosing,
-            b"SI" => IssueSubType::SpotRateIntraday,
-            b"T " => IssueSubType::TrackingStock,
-            b"TC" => IssueSubType::TrustCertificates,
-            b"TU" => IssueSubType::TrustUnits,
-            b"U " => IssueSubType::Portal,
-            b"V " => IssueSubType::ContingentValueRight,
-            b"W " => IssueSubType::TrustIssuedReceipts,
-            b"WC" => IssueSubType::WorldCurrencyOption,
-            b"X " => IssueSubType::Trust,
-            b"Y " => IssueSubType::Other,
-            b"Z " => IssueSubType::NotApplicable,
-            _ => unreachable!(),
-        }
-    }
-}
-
-#[derive(Debug, PartialEq, Eq)]
+            b"A " => Ok(IssueSubType::PreferredTrustSecurities),
+            b"AI" => Ok(IssueSubType::AlphaIndexETNs),
+            b"B " => Ok(IssueSubType::IndexBasedDerivative),
+            b"C " => Ok(IssueSubType::CommonShares),
+            b"CB" => Ok(IssueSubType::CommodityBasedTrustShares),
+            b"CF" => Ok(IssueSubType::CommodityFuturesTrustShares),
+            b"CL" => Ok(IssueSubType::CommodityLinkedSecurities),
+            b"CM" => Ok(IssueSubType::CommodityIndexTrustShares),
+            b"CO" => Ok(IssueSubType::CollateralizedMortgageObligation),
+            b"CT" => Ok(IssueSubType::CurrencyTrustShares),
+            b"CU" => Ok(IssueSubType::CommodityCurrencyLinkedSecurities),
+            b"CW" => Ok(IssueSubType::CurrencyWarrants),
+            b"D " => Ok(IssueSubType::GlobalDepositaryShares),
+            b"E " => Ok(IssueSubType::ETFPortfolioDepositaryReceipt),
+            b"EG" => Ok(IssueSubType::EquityGoldShares),
+            b"EI" => Ok(IssueSubType::ETNEquityIndexLinkedSecurities),
+            b"EM" => Ok(IssueSubType::ExchangeTradedManagedFunds),
+            b"EN" => Ok(IssueSubType::ExchangeTradedNotes),
+            b"EU" => Ok(IssueSubType::EquityUnits),
+            b"F " => Ok(IssueSubType::Holdrs),
+            b"FI" => Ok(IssueSubType::ETNFixedIncomeLinkedSecurities),
+            b"FL" => Ok(IssueSubType::ETNFuturesLinkedSecurities),
+            b"G " => Ok(IssueSubType::GlobalShares),
+            b"I " => Ok(IssueSubType::ETFIndexFundShares),
+            b"IR" => Ok(IssueSubType::InterestRate),
+            b"IW" => Ok(IssueSubType::IndexWarrant),
+            b"IX" => Ok(IssueSubType::IndexLinkedExchangeableNotes),
+            b"J " => Ok(IssueSubType::CorporateBackedTrustSecurity),
+            b"L " => Ok(IssueSubType::ContingentLitigationRight),
+            b"LL" => Ok(IssueSubType::Llc),
+            b"M " => Ok(IssueSubType::EquityBasedDerivative),
+            b"MF" => Ok(IssueSubType::ManagedFundShares),
+            b"ML" => Ok(IssueSubType::ETNMultiFactorIndexLinkedSecurities),
+            b"MT" => Ok(IssueSubType::ManagedTrustSecurities),
+            b"N " => Ok(IssueSubType::NYRegistryShares),
+            b"O " => Ok(IssueSubType::OpenEndedMutualFund),
+            b"P " => Ok(IssueSubType::PrivatelyHeldSecurity),
+            b"PP" => Ok(IssueSubType::PoisonPill),
+            b"PU" => Ok(IssueSubType::PartnershipUnits),
+            b"Q " => Ok(IssueSubType::ClosedEndFunds),
+            b"R " => Ok(IssueSubType::RegS),
+            b"RC" => Ok(IssueSubType::CommodityRedeemableCommodityLinkedSecurities),
+            b"RF" => Ok(IssueSubType::ETNRedeemableFuturesLinkedSecurities),
+            b"RT" => Ok(IssueSubType::Reit),
+            b"RU" => Ok(IssueSubType::CommodityRedeemableCurrencyLinkedSecurities),
+            b"S " => Ok(IssueSubType::Seed),
+            b"SC" => Ok(IssueSubType::SpotRateClosing),
+            b"SI" => Ok(IssueSubType::SpotRateIntraday),
+            b"T " => Ok(IssueSubType::TrackingStock),
+            b"TC" => Ok(IssueSubType::TrustCertificates),
+            b"TU" => Ok(IssueSubType::TrustUnits),
+            b"U " => Ok(IssueSubType::Portal),
+            b"V " => Ok(IssueSubType::ContingentValueRight),
+            b"W " => Ok(IssueSubType::TrustIssuedReceipts),
+            b"WC" => Ok(IssueSubType::WorldCurrencyOption),
+            b"X " => Ok(IssueSubType::Trust),
+            b"Y " => Ok(IssueSubType::Other),
+            b"Z " => Ok(IssueSubType::NotApplicable),
+            _ => Err(DecodeError::InvalidValue(bytes[0])),
+        }
+    }
+
+    pub fn to_bytes(&self) -> &'static [u8; 2] {
+        match self {
+            IssueSubType::PreferredTrustSecurities => b"A ",
+            IssueSubType::AlphaIndexETNs => b"AI",
+            IssueSubType::IndexBasedDerivative => b"B ",
+            IssueSubType::CommonShares => b"C ",
+            IssueSubType::CommodityBasedTrustShares => b"CB",
+            IssueSubType::CommodityFuturesTrustShares => b"CF",
+            IssueSubType::CommodityLinkedSecurities => b"CL",
+            IssueSubType::CommodityIndexTrustShares => b"CM",
+            IssueSubType::CollateralizedMortgageObligation => b"CO",
+            IssueSubType::CurrencyTrustShares => b"CT",
+            IssueSubType::CommodityCurrencyLinkedSecurities => b"CU",
+            IssueSubType::CurrencyWarrants => b"CW",
+            IssueSubType::GlobalDepositaryShares => b"D ",
+            IssueSubType::ETFPortfolioDepositaryReceipt => b"E ",
+            IssueSubType::EquityGoldShares => b"EG",
+            IssueSubType::ETNEquityIndexLinkedSecurities => b"EI",
+            IssueSubType::ExchangeTradedManagedFunds => b"EM",
+            IssueSubType::ExchangeTradedNotes => b"EN",
+            IssueSubType::EquityUnits => b"EU",
+            IssueSubType::Holdrs => b"F ",
+            IssueSubType::ETNFixedIncomeLinkedSecurities => b"FI",
+            IssueSubType::ETNFuturesLinkedSecurities => b"FL",
+            IssueSubType::GlobalShares => b"G ",
+            IssueSubType::ETFIndexFundShares => b"I ",
+            IssueSubType::InterestRate => b"IR",
+            IssueSubType::IndexWarrant => b"IW",
+            IssueSubType::IndexLinkedExchangeableNotes => b"IX",
+            IssueSubType::CorporateBackedTrustSecurity => b"J ",
+            IssueSubType::ContingentLitigationRight => b"L ",
+            IssueSubType::Llc => b"LL",
+            IssueSubType::EquityBasedDerivative => b"M ",
+            IssueSubType::ManagedFundShares => b"MF",
+            IssueSubType::ETNMultiFactorIndexLinkedSecurities => b"ML",
+            IssueSubType::ManagedTrustSecurities => b"MT",
+            IssueSubType::NYRegistryShares => b"N ",
+            IssueSubType::OpenEndedMutualFund => b"O ",
+            IssueSubType::PrivatelyHeldSecurity => b"P ",
+            IssueSubType::PoisonPill => b"PP",
+            IssueSubType::PartnershipUnits => b"PU",
+            IssueSubType::ClosedEndFunds => b"Q ",
+            IssueSubType::RegS => b"R ",
+            IssueSubType::CommodityRedeemableCommodityLinkedSecurities => b"RC",
+            IssueSubType::ETNRedeemableFuturesLinkedSecurities => b"RF",
+            IssueSubType::Reit => b"RT",
+            IssueSubType::CommodityRedeemableCurrencyLinkedSecurities => b"RU",
+            IssueSubType::Seed => b"S ",
+            IssueSubType::SpotRateClosing => b"SC",
+            IssueSubType::SpotRateIntraday => b"SI",
+            IssueSubType::TrackingStock => b"T ",
+            IssueSubType::TrustCertificates => b"TC",
+            IssueSubType::TrustUnits => b"TU",
+            IssueSubType::Portal => b"U ",
+            IssueSubType::ContingentValueRight => b"V ",
+            IssueSubType::TrustIssuedReceipts => b"W ",
+            IssueSubType::WorldCurrencyOption => b"WC",
+            IssueSubType::Trust => b"X ",
+            IssueSubType::Other => b"Y ",
+            IssueSubType::NotApplicable => b"Z ",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum Authenticity {
     Production,
     Test,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+impl Authenticity {
+    pub fn new(byte: u8) -> Result<Self, DecodeError> {
+        match byte {
+            b'P' => Ok(Authenticity::Production),
+            b'T' => Ok(Authenticity::Test),
+            _ => Err(DecodeError::InvalidValue(byte)),
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            Authenticity::Production => b'P',
+            Authenticity::Test => b'T',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum ThresholdIndicator {
     Restricted,
     NotRestricted,
     Unavailable,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+impl ThresholdIndicator {
+    pub fn new(byte: u8) -> Result<Self, DecodeError> {
+        match byte {
+            b'Y' => Ok(ThresholdIndicator::Restricted),
+            b'N' => Ok(ThresholdIndicator::NotRestricted),
+            b' ' => Ok(ThresholdIndicator::Unavailable),
+            _ => Err(DecodeError::InvalidValue(byte)),
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            ThresholdIndicator::Restricted => b'Y',
+            ThresholdIndicator::NotRestricted => b'N',
+            ThresholdIndicator::Unavailable => b' ',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum TradingState {
+    Halted,
+    Paused,
+    QuotationOnly,
+    Trading,
+}
+
+impl TradingState {
+    pub fn new(byte: u8) -> Result<Self, DecodeError> {
+        match byte {
+            b'H' => Ok(TradingState::Halted),
+            b'P' => Ok(TradingState::Paused),
+            b'Q' => Ok(TradingState::QuotationOnly),
+            b'T' => Ok(TradingState::Trading),
+            _ => Err(DecodeError::InvalidValue(byte)),
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            TradingState::Halted => b'H',
+            TradingState::Paused => b'P',
+            TradingState::QuotationOnly => b'Q',
+            TradingState::Trading => b'T',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum RegShoAction {
+    NoPriceTest,
+    RestrictionInEffect,
+    RestrictionRemains,
+}
+
+impl RegShoAction {
+    pub fn new(byte: u8) -> Result<Self, DecodeError> {
+        match byte {
+            b'0' => Ok(RegShoAction::NoPriceTest),
+            b'1' => Ok(RegShoAction::RestrictionInEffect),
+            b'2' => Ok(RegShoAction::RestrictionRemains),
+            _ => Err(DecodeError::InvalidValue(byte)),
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            RegShoAction::NoPriceTest => b'0',
+            RegShoAction::RestrictionInEffect => b'1',
+            RegShoAction::RestrictionRemains => b'2',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum IpoFlag {
     New,
     NotNew,
     Unavailable,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+impl IpoFlag {
+    pub fn new(byte: u8) -> Result<Self, DecodeError> {
+        match byte {
+            b'Y' => Ok(IpoFlag::New),
+            b'N' => Ok(IpoFlag::NotNew),
+            b' ' => Ok(IpoFlag::Unavailable),
+            _ => Err(DecodeError::InvalidValue(byte)),
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            IpoFlag::New => b'Y',
+            IpoFlag::NotNew => b'N',
+            IpoFlag::Unavailable => b' ',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum LuldRefPriceTier {
     Tier1,
     Tier2,
     Unavailable,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+impl LuldRefPriceTier {
+    pub fn new(byte: u8) -> Result<Self, DecodeError> {
+        match byte {
+            b'1' => Ok(LuldRefPriceTier::Tier1),
+            b'2' => Ok(LuldRefPriceTier::Tier2),
+            b' ' => Ok(LuldRefPriceTier::Unavailable),
+            _ => Err(DecodeError::InvalidValue(byte)),
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            LuldRefPriceTier::Tier1 => b'1',
+            LuldRefPriceTier::Tier2 => b'2',
+            LuldRefPriceTier::Unavailable => b' ',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum EtpFlag {
     Is,
     IsNot,
     Unavailable,
 }
+
+impl EtpFlag {
+    pub fn new(byte: u8) -> Result<Self, DecodeError> {
+        match byte {
+            b'Y' => Ok(EtpFlag::Is),
+            b'N' => Ok(EtpFlag::IsNot),
+            b' ' => Ok(EtpFlag::Unavailable),
+            _ => Err(DecodeError::InvalidValue(byte)),
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            EtpFlag::Is => b'Y',
+            EtpFlag::IsNot => b'N',
+            EtpFlag::Unavailable => b' ',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum MarketMakerMode {
+    Normal,
+    Passive,
+    Syndicate,
+    PreSyndicate,
+    Penalty,
+}
+
+impl MarketMakerMode {
+    pub fn new(byte: u8) -> Result<Self, DecodeError> {
+        match byte {
+            b'N' => Ok(MarketMakerMode::Normal),
+            b'P' => Ok(MarketMakerMode::Passive),
+            b'S' => Ok(MarketMakerMode::Syndicate),
+            b'R' => Ok(MarketMakerMode::PreSyndicate),
+            b'L' => Ok(MarketMakerMode::Penalty),
+            _ => Err(DecodeError::InvalidValue(byte)),
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            MarketMakerMode::Normal => b'N',
+            MarketMakerMode::Passive => b'P',
+            MarketMakerMode::Syndicate => b'S',
+            MarketMakerMode::PreSyndicate => b'R',
+            MarketMakerMode::Penalty => b'L',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum MarketParticipantState {
+    Active,
+    Excused,
+    Withdrawn,
+    Suspended,
+    Deleted,
+}
+
+impl MarketParticipantState {
+    pub fn new(byte: u8) -> Result<Self, DecodeError> {
+        match byte {
+            b'A' => Ok(MarketParticipantState::Active),
+            b'E' => Ok(MarketParticipantState::Excused),
+            b'W' => Ok(MarketParticipantState::Withdrawn),
+            b'S' => Ok(MarketParticipantState::Suspended),
+            b'D' => Ok(MarketParticipantState::Deleted),
+            _ => Err(DecodeError::InvalidValue(byte)),
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            MarketParticipantState::Active => b'A',
+            MarketParticipantState::Excused => b'E',
+            MarketParticipantState::Withdrawn => b'W',
+            MarketParticipantState::Suspended => b'S',
+            MarketParticipantState::Deleted => b'D',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum MwcbLevel {
+    Level1,
+    Level2,
+    Level3,
+}
+
+impl MwcbLevel {
+    pub fn new(byte: u8) -> Result<Self, DecodeError> {
+        match byte {
+            b'1' => Ok(MwcbLevel::Level1),
+            b'2' => Ok(MwcbLevel::Level2),
+            b'3' => Ok(MwcbLevel::Level3),
+            _ => Err(DecodeError::InvalidValue(byte)),
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            MwcbLevel::Level1 => b'1',
+            MwcbLevel::Level2 => b'2',
+            MwcbLevel::Level3 => b'3',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum IpoReleaseQualifier {
+    Anticipated,
+    Canceled,
+}
+
+impl IpoReleaseQualifier {
+    pub fn new(byte: u8) -> Result<Self, DecodeError> {
+        match byte {
+            b'A' => Ok(IpoReleaseQualifier::Anticipated),
+            b'C' => Ok(IpoReleaseQualifier::Canceled),
+            _ => Err(DecodeError::InvalidValue(byte)),
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            IpoReleaseQualifier::Anticipated => b'A',
+            IpoReleaseQualifier::Canceled => b'C',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum MarketCode {
+    Nasdaq,
+    Bx,
+    Psx,
+}
+
+impl MarketCode {
+    pub fn new(byte: u8) -> Result<Self, DecodeError> {
+        match byte {
+            b'Q' => Ok(MarketCode::Nasdaq),
+            b'B' => Ok(MarketCode::Bx),
+            b'X' => Ok(MarketCode::Psx),
+            _ => Err(DecodeError::InvalidValue(byte)),
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            MarketCode::Nasdaq => b'Q',
+            MarketCode::Bx => b'B',
+            MarketCode::Psx => b'X',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum OperationalHaltAction {
+    Halted,
+    Resumed,
+}
+
+impl OperationalHaltAction {
+    pub fn new(byte: u8) -> Result<Self, DecodeError> {
+        match byte {
+            b'H' => Ok(OperationalHaltAction::Halted),
+            b'T' => Ok(OperationalHaltAction::Resumed),
+            _ => Err(DecodeError::InvalidValue(byte)),
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            OperationalHaltAction::Halted => b'H',
+            OperationalHaltAction::Resumed => b'T',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum CrossType {
+    Opening,
+    Closing,
+    IpoOrHalted,
+    Intraday,
+    ExtendedTradingClose,
+}
+
+impl CrossType {
+    pub fn new(byte: u8) -> Result<Self, DecodeError> {
+        match byte {
+            b'O' => Ok(CrossType::Opening),
+            b'C' => Ok(CrossType::Closing),
+            b'H' => Ok(CrossType::IpoOrHalted),
+            b'I' => Ok(CrossType::Intraday),
+            b'A' => Ok(CrossType::ExtendedTradingClose),
+            _ => Err(DecodeError::InvalidValue(byte)),
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            CrossType::Opening => b'O',
+            CrossType::Closing => b'C',
+            CrossType::IpoOrHalted => b'H',
+            CrossType::Intraday => b'I',
+            CrossType::ExtendedTradingClose => b'A',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ImbalanceDirection {
+    Buy,
+    Sell,
+    NoImbalance,
+    InsufficientOrders,
+    Paused,
+}
+
+impl ImbalanceDirection {
+    pub fn new(byte: u8) -> Result<Self, DecodeError> {
+        match byte {
+            b'B' => Ok(ImbalanceDirection::Buy),
+            b'S' => Ok(ImbalanceDirection::Sell),
+            b'N' => Ok(ImbalanceDirection::NoImbalance),
+            b'O' => Ok(ImbalanceDirection::InsufficientOrders),
+            b'P' => Ok(ImbalanceDirection::Paused),
+            _ => Err(DecodeError::InvalidValue(byte)),
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            ImbalanceDirection::Buy => b'B',
+            ImbalanceDirection::Sell => b'S',
+            ImbalanceDirection::NoImbalance => b'N',
+            ImbalanceDirection::InsufficientOrders => b'O',
+            ImbalanceDirection::Paused => b'P',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum PriceVariationIndicator {
+    LessThan1Percent,
+    From1To2Percent,
+    From2To3Percent,
+    From3To4Percent,
+    From4To5Percent,
+    From5To6Percent,
+    From6To7Percent,
+    From7To8Percent,
+    From8To9Percent,
+    From9To10Percent,
+    From10To20Percent,
+    From20To30Percent,
+    From30Percent,
+    Unavailable,
+}
+
+impl PriceVariationIndicator {
+    pub fn new(byte: u8) -> Result<Self, DecodeError> {
+        match byte {
+            b'L' => Ok(PriceVariationIndicator::LessThan1Percent),
+            b'1' => Ok(PriceVariationIndicator::From1To2Percent),
+            b'2' => Ok(PriceVariationIndicator::From2To3Percent),
+            b'3' => Ok(PriceVariationIndicator::From3To4Percent),
+            b'4' => Ok(PriceVariationIndicator::From4To5Percent),
+            b'5' => Ok(PriceVariationIndicator::From5To6Percent),
+            b'6' => Ok(PriceVariationIndicator::From6To7Percent),
+            b'7' => Ok(PriceVariationIndicator::From7To8Percent),
+            b'8' => Ok(PriceVariationIndicator::From8To9Percent),
+            b'9' => Ok(PriceVariationIndicator::From9To10Percent),
+            b'A' => Ok(PriceVariationIndicator::From10To20Percent),
+            b'B' => Ok(PriceVariationIndicator::From20To30Percent),
+            b'C' => Ok(PriceVariationIndicator::From30Percent),
+            b' ' => Ok(PriceVariationIndicator::Unavailable),
+            _ => Err(DecodeError::InvalidValue(byte)),
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            PriceVariationIndicator::LessThan1Percent => b'L',
+            PriceVariationIndicator::From1To2Percent => b'1',
+            PriceVariationIndicator::From2To3Percent => b'2',
+            PriceVariationIndicator::From3To4Percent => b'3',
+            PriceVariationIndicator::From4To5Percent => b'4',
+            PriceVariationIndicator::From5To6Percent => b'5',
+            PriceVariationIndicator::From6To7Percent => b'6',
+            PriceVariationIndicator::From7To8Percent => b'7',
+            PriceVariationIndicator::From8To9Percent => b'8',
+            PriceVariationIndicator::From9To10Percent => b'9',
+            PriceVariationIndicator::From10To20Percent => b'A',
+            PriceVariationIndicator::From20To30Percent => b'B',
+            PriceVariationIndicator::From30Percent => b'C',
+            PriceVariationIndicator::Unavailable => b' ',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum RetailInterest {
+    Buy,
+    Sell,
+    Both,
+    NoInterest,
+}
+
+impl RetailInterest {
+    pub fn new(byte: u8) -> Result<Self, DecodeError> {
+        match byte {
+            b'B' => Ok(RetailInterest::Buy),
+            b'S' => Ok(RetailInterest::Sell),
+            b'A' => Ok(RetailInterest::Both),
+            b'N' => Ok(RetailInterest::NoInterest),
+            _ => Err(DecodeError::InvalidValue(byte)),
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        match self {
+            RetailInterest::Buy => b'B',
+            RetailInterest::Sell => b'S',
+            RetailInterest::Both => b'A',
+            RetailInterest::NoInterest => b'N',
+        }
+    }
+}
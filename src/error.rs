@@ -0,0 +1,48 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    Truncated { expected: usize, actual: usize },
+    InvalidValue(u8),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Truncated { expected, actual } => {
+                write!(
+                    f,
+                    "truncated input: expected {} bytes, got {}",
+                    expected, actual
+                )
+            }
+            DecodeError::InvalidValue(byte) => write!(f, "invalid field value: {:#04x}", byte),
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    kind: &'static str,
+    input: String,
+}
+
+impl ParseError {
+    pub fn new(kind: &'static str, input: &str) -> Self {
+        ParseError {
+            kind,
+            input: input.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid {}: {:?}", self.kind, self.input)
+    }
+}
+
+impl Error for ParseError {}
@@ -0,0 +1,731 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::enums::Side;
+use crate::messages::{Alpha, Body, Message, MessageView};
+use crate::pipeline::Pipeline;
+use crate::price::{Price4, Price8};
+use crate::timestamp::Timestamp;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+
+impl Format {
+    fn extension(&self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            #[cfg(feature = "parquet")]
+            Format::Parquet => "parquet",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    UInt,
+    Price,
+    Price8,
+    Bool,
+    Text,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    UInt(u64),
+    Price(Price4),
+    Price8(Price8),
+    Bool(bool),
+    Text(String),
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+impl From<u64> for Value {
+    fn from(value: u64) -> Self {
+        Value::UInt(value)
+    }
+}
+
+impl From<Price4> for Value {
+    fn from(value: Price4) -> Self {
+        Value::Price(value)
+    }
+}
+
+impl From<Price8> for Value {
+    fn from(value: Price8) -> Self {
+        Value::Price8(value)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::Text(value)
+    }
+}
+
+pub trait Table {
+    fn write_row(&mut self, row: &[Value]) -> io::Result<()>;
+    fn finish(self: Box<Self>) -> io::Result<()>;
+}
+
+#[cfg_attr(not(feature = "parquet"), allow(unused_variables))]
+pub fn create_table(
+    path: &Path,
+    format: Format,
+    name: &str,
+    columns: Vec<(String, Kind)>,
+) -> io::Result<Box<dyn Table>> {
+    match format {
+        Format::Csv => Ok(Box::new(CsvTable::new(path, &columns)?)),
+        #[cfg(feature = "parquet")]
+        Format::Parquet => Ok(Box::new(ParquetTable::new(path, name, columns)?)),
+    }
+}
+
+struct CsvTable {
+    out: BufWriter<File>,
+}
+
+impl CsvTable {
+    fn new(path: &Path, columns: &[(String, Kind)]) -> io::Result<Self> {
+        let mut out = BufWriter::new(File::create(path)?);
+        let names: Vec<&str> = columns.iter().map(|(name, _)| name.as_str()).collect();
+        writeln!(out, "{}", names.join(","))?;
+        Ok(CsvTable { out })
+    }
+}
+
+impl Table for CsvTable {
+    fn write_row(&mut self, row: &[Value]) -> io::Result<()> {
+        for (index, value) in row.iter().enumerate() {
+            if index > 0 {
+                self.out.write_all(b",")?;
+            }
+            match value {
+                Value::Null => (),
+                Value::UInt(value) => write!(self.out, "{}", value)?,
+                Value::Price(price) => write!(self.out, "{}", price)?,
+                Value::Price8(price) => write!(self.out, "{}", price)?,
+                Value::Bool(value) => write!(self.out, "{}", value)?,
+                // Alpha fields may hold commas or quotes on malformed feeds.
+                Value::Text(text) if text.contains([',', '"', '\n']) => {
+                    write!(self.out, "\"{}\"", text.replace('"', "\"\""))?
+                }
+                Value::Text(text) => self.out.write_all(text.as_bytes())?,
+            }
+        }
+        self.out.write_all(b"\n")
+    }
+
+    fn finish(mut self: Box<Self>) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+#[cfg(feature = "parquet")]
+const ROW_GROUP_SIZE: usize = 64 * 1024;
+
+// Every column is optional. Unsigned integers are INT64 annotated as
+// unsigned and prices INT64 decimals keeping the 4 or 8 implied decimals of
+// their ITCH field.
+#[cfg(feature = "parquet")]
+struct ParquetTable {
+    writer: parquet::file::writer::SerializedFileWriter<File>,
+    columns: Vec<(String, Kind)>,
+    rows: Vec<Vec<Value>>,
+}
+
+#[cfg(feature = "parquet")]
+impl ParquetTable {
+    fn new(path: &Path, name: &str, columns: Vec<(String, Kind)>) -> io::Result<Self> {
+        use std::sync::Arc;
+
+        use parquet::file::properties::WriterProperties;
+        use parquet::file::writer::SerializedFileWriter;
+        use parquet::schema::parser::parse_message_type;
+
+        let fields: Vec<String> = columns
+            .iter()
+            .map(|(name, kind)| match kind {
+                Kind::UInt => format!("optional int64 {} (INTEGER(64,false));", name),
+                Kind::Price => format!(
+                    "optional int64 {} (DECIMAL(18,{}));",
+                    name,
+                    Price4::DECIMALS
+                ),
+                Kind::Price8 => {
+                    format!(
+                        "optional int64 {} (DECIMAL(18,{}));",
+                        name,
+                        Price8::DECIMALS
+                    )
+                }
+                Kind::Bool => format!("optional boolean {};", name),
+                Kind::Text => format!("optional binary {} (STRING);", name),
+            })
+            .collect();
+        let schema = parse_message_type(&format!("message {} {{ {} }}", name, fields.join(" ")))
+            .map_err(io::Error::other)?;
+        let writer = SerializedFileWriter::new(
+            File::create(path)?,
+            Arc::new(schema),
+            Arc::new(WriterProperties::builder().build()),
+        )
+        .map_err(io::Error::other)?;
+        Ok(ParquetTable {
+            writer,
+            columns,
+            rows: Vec::with_capacity(ROW_GROUP_SIZE),
+        })
+    }
+
+    fn write_row_group(&mut self) -> parquet::errors::Result<()> {
+        use parquet::data_type::{BoolType, ByteArray, ByteArrayType, Int64Type};
+        use parquet::errors::ParquetError;
+
+        let mut row_group = self.writer.next_row_group()?;
+        let mut index = 0;
+        while let Some(mut column) = row_group.next_column()? {
+            let values = self.rows.iter().map(|row| &row[index]);
+            let levels: Vec<i16> = values
+                .clone()
+                .map(|value| (*value != Value::Null) as i16)
+                .collect();
+            match self.columns[index].1 {
+                Kind::UInt => {
+                    let values: Vec<i64> = values
+                        .filter_map(|value| match value {
+                            Value::UInt(value) => Some(*value as i64),
+                            _ => None,
+                        })
+                        .collect();
+                    column
+                        .typed::<Int64Type>()
+                        .write_batch(&values, Some(&levels), None)?;
+                }
+                Kind::Price => {
+                    let values: Vec<i64> = values
+                        .filter_map(|value| match value {
+                            Value::Price(price) => Some(price.0 as i64),
+                            _ => None,
+                        })
+                        .collect();
+                    column
+                        .typed::<Int64Type>()
+                        .write_batch(&values, Some(&levels), None)?;
+                }
+                Kind::Price8 => {
+                    let values = values
+                        .filter_map(|value| match value {
+                            Value::Price8(price) => Some(*price),
+                            _ => None,
+                        })
+                        .map(|price| match i64::try_from(price.0) {
+                            Ok(value) if value < 1_000_000_000_000_000_000 => Ok(value),
+                            _ => Err(ParquetError::General(format!(
+                                "{} does not fit DECIMAL(18,8)",
+                                price
+                            ))),
+                        })
+                        .collect::<Result<Vec<i64>, _>>()?;
+                    column
+                        .typed::<Int64Type>()
+                        .write_batch(&values, Some(&levels), None)?;
+                }
+                Kind::Bool => {
+                    let values: Vec<bool> = values
+                        .filter_map(|value| match value {
+                            Value::Bool(value) => Some(*value),
+                            _ => None,
+                        })
+                        .collect();
+                    column
+                        .typed::<BoolType>()
+                        .write_batch(&values, Some(&levels), None)?;
+                }
+                Kind::Text => {
+                    let values: Vec<ByteArray> = values
+                        .filter_map(|value| match value {
+                            Value::Text(text) => Some(ByteArray::from(text.as_str())),
+                            _ => None,
+                        })
+                        .collect();
+                    column
+                        .typed::<ByteArrayType>()
+                        .write_batch(&values, Some(&levels), None)?;
+                }
+            }
+            column.close()?;
+            index += 1;
+        }
+        row_group.close()?;
+        self.rows.clear();
+        Ok(())
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl Table for ParquetTable {
+    fn write_row(&mut self, row: &[Value]) -> io::Result<()> {
+        self.rows.push(row.to_vec());
+        if self.rows.len() == ROW_GROUP_SIZE {
+            self.write_row_group().map_err(io::Error::other)?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> io::Result<()> {
+        if !self.rows.is_empty() {
+            self.write_row_group().map_err(io::Error::other)?;
+        }
+        self.writer.close().map_err(io::Error::other)?;
+        Ok(())
+    }
+}
+
+fn columns(names: &[(&str, Kind)]) -> Vec<(String, Kind)> {
+    names
+        .iter()
+        .map(|(name, kind)| (name.to_string(), *kind))
+        .collect()
+}
+
+// One row per symbol and snapshot, the levels of both sides side by side.
+fn snapshot_columns(book_depth: usize) -> Vec<(String, Kind)> {
+    let mut columns = columns(&[
+        ("symbol", Kind::Text),
+        ("stock_locate", Kind::UInt),
+        ("sequence_number", Kind::UInt),
+        ("timestamp", Kind::UInt),
+        ("capture_timestamp", Kind::UInt),
+        ("trading_state", Kind::Text),
+    ]);
+    for level in 1..=book_depth {
+        for side in ["bid", "ask"] {
+            columns.push((format!("{}_price_{}", side, level), Kind::Price));
+            columns.push((format!("{}_shares_{}", side, level), Kind::UInt));
+            columns.push((format!("{}_orders_{}", side, level), Kind::UInt));
+        }
+    }
+    columns
+}
+
+const TRADE_COLUMNS: [(&str, Kind); 9] = [
+    ("symbol", Kind::Text),
+    ("stock_locate", Kind::UInt),
+    ("timestamp", Kind::UInt),
+    ("price", Kind::Price),
+    ("shares", Kind::UInt),
+    ("match_number", Kind::UInt),
+    ("printable", Kind::Bool),
+    ("cross_type", Kind::Text),
+    ("capture_timestamp", Kind::UInt),
+];
+
+// Header columns are set for every message, the others only by the message
+// types carrying them. Fields sharing a meaning across types share a column.
+const EVENT_COLUMNS: [(&str, Kind); 65] = [
+    ("sequence_number", Kind::UInt),
+    ("capture_timestamp", Kind::UInt),
+    ("timestamp", Kind::UInt),
+    ("msg_type", Kind::Text),
+    ("stock_locate", Kind::UInt),
+    ("tracking_number", Kind::UInt),
+    ("stock", Kind::Text),
+    ("reference", Kind::UInt),
+    ("new_reference", Kind::UInt),
+    ("side", Kind::Text),
+    ("shares", Kind::UInt),
+    ("price", Kind::Price),
+    ("match_number", Kind::UInt),
+    ("printable", Kind::Bool),
+    ("attribution", Kind::Text),
+    ("cross_type", Kind::Text),
+    ("paired_shares", Kind::UInt),
+    ("imbalance_shares", Kind::UInt),
+    ("imbalance_direction", Kind::Text),
+    ("far_price", Kind::Price),
+    ("near_price", Kind::Price),
+    ("reference_price", Kind::Price),
+    ("price_variation_indicator", Kind::Text),
+    ("trading_state", Kind::Text),
+    ("reason", Kind::Text),
+    ("event_code", Kind::Text),
+    ("market_category", Kind::Text),
+    ("financial_status", Kind::Text),
+    ("round_lot_size", Kind::UInt),
+    ("round_lots_only", Kind::Bool),
+    ("issue_classification", Kind::Text),
+    ("issue_subtype", Kind::Text),
+    ("authenticity", Kind::Text),
+    ("short_sale_threshold", Kind::Text),
+    ("ipo_flag", Kind::Text),
+    ("luld_ref_price_tier", Kind::Text),
+    ("etp_flag", Kind::Text),
+    ("etp_leverage_factor", Kind::UInt),
+    ("inverse_indicator", Kind::Bool),
+    ("reg_sho_action", Kind::Text),
+    ("mpid", Kind::Text),
+    ("primary_market_maker", Kind::Bool),
+    ("market_maker_mode", Kind::Text),
+    ("market_participant_state", Kind::Text),
+    ("mwcb_level_1", Kind::Price8),
+    ("mwcb_level_2", Kind::Price8),
+    ("mwcb_level_3", Kind::Price8),
+    ("breached_level", Kind::Text),
+    ("release_time", Kind::UInt),
+    ("release_qualifier", Kind::Text),
+    ("ipo_price", Kind::Price),
+    ("upper_price", Kind::Price),
+    ("lower_price", Kind::Price),
+    ("extension", Kind::UInt),
+    ("market_code", Kind::Text),
+    ("halt_action", Kind::Text),
+    ("open_eligible", Kind::Bool),
+    ("min_allowable_price", Kind::Price),
+    ("max_allowable_price", Kind::Price),
+    ("near_execution_price", Kind::Price),
+    ("near_execution_time", Kind::UInt),
+    ("lower_price_range_collar", Kind::Price),
+    ("upper_price_range_collar", Kind::Price),
+    ("interest", Kind::Text),
+    // Bytes after the type of messages the decoder does not model, in hex.
+    ("raw", Kind::Text),
+];
+
+const EVENT_HEADER_COLUMNS: usize = 6;
+
+fn alpha<const N: usize>(value: &Alpha<N>) -> String {
+    value.to_string().trim_end().to_string()
+}
+
+fn name(value: impl Debug) -> Value {
+    Value::Text(format!("{:?}", value))
+}
+
+// The columns set by a message besides the header, with their values.
+fn event_fields(body: &Body) -> Vec<(&'static str, Value)> {
+    let text = |value: String| Value::Text(value);
+    let uint = |value: u32| Value::UInt(value as u64);
+    match body {
+        Body::AddOrder(add) => vec![
+            ("stock", text(alpha(&add.stock))),
+            ("reference", add.reference.into()),
+            ("side", name(add.side)),
+            ("shares", uint(add.shares)),
+            ("price", add.price.into()),
+            (
+                "attribution",
+                add.attribution.map(|mpid| alpha(&mpid)).into(),
+            ),
+        ],
+        Body::ExecutedOrder(exec) => vec![
+            ("reference", exec.reference.into()),
+            ("shares", uint(exec.executed_shares)),
+            ("match_number", exec.match_number.into()),
+        ],
+        Body::ExecutedWithPriceOrder(exec) => vec![
+            ("reference", exec.reference.into()),
+            ("shares", uint(exec.executed_shares)),
+            ("price", exec.price.into()),
+            ("match_number", exec.match_number.into()),
+            ("printable", exec.printable.into()),
+        ],
+        Body::CancelOrder(cancel) => vec![
+            ("reference", cancel.reference.into()),
+            ("shares", uint(cancel.canceled_shares)),
+        ],
+        Body::DeleteOrder(delete) => vec![("reference", delete.reference.into())],
+        Body::ReplaceOrder(replace) => vec![
+            ("reference", replace.original_reference.into()),
+            ("new_reference", replace.new_reference.into()),
+            ("shares", uint(replace.shares)),
+            ("price", replace.price.into()),
+        ],
+        Body::NonCrossTrade(trade) => vec![
+            ("stock", text(alpha(&trade.stock))),
+            ("reference", trade.reference.into()),
+            ("side", name(trade.side)),
+            ("shares", uint(trade.shares)),
+            ("price", trade.price.into()),
+            ("match_number", trade.match_number.into()),
+        ],
+        Body::CrossTrade(cross) => vec![
+            ("stock", text(alpha(&cross.stock))),
+            ("shares", cross.shares.into()),
+            ("price", cross.cross_price.into()),
+            ("match_number", cross.match_number.into()),
+            ("cross_type", name(cross.cross_type)),
+        ],
+        Body::BrokenTrade(broken) => vec![("match_number", broken.match_number.into())],
+        Body::NetOrderImbalance(noii) => vec![
+            ("stock", text(alpha(&noii.stock))),
+            ("cross_type", name(noii.cross_type)),
+            ("paired_shares", noii.paired_shares.into()),
+            ("imbalance_shares", noii.imbalance_shares.into()),
+            ("imbalance_direction", name(noii.imbalance_direction)),
+            ("far_price", noii.far_price.into()),
+            ("near_price", noii.near_price.into()),
+            ("reference_price", noii.current_reference_price.into()),
+            (
+                "price_variation_indicator",
+                name(noii.price_variation_indicator),
+            ),
+        ],
+        Body::StockTradingAction(action) => vec![
+            ("stock", text(alpha(&action.stock))),
+            ("trading_state", name(action.trading_state)),
+            ("reason", text(alpha(&action.reason))),
+        ],
+        Body::StockDirectory(dir) => vec![
+            ("stock", text(alpha(&dir.stock))),
+            ("market_category", name(dir.market_category)),
+            ("financial_status", name(dir.financial_status)),
+            ("round_lot_size", uint(dir.round_lot_size)),
+            ("round_lots_only", dir.round_lots_only.into()),
+            ("issue_classification", name(dir.issue_classification)),
+            ("issue_subtype", name(dir.issue_subtype)),
+            ("authenticity", name(dir.authenticity)),
+            ("short_sale_threshold", name(dir.short_sale_threshold)),
+            ("ipo_flag", name(dir.ipo_flag)),
+            ("luld_ref_price_tier", name(dir.luld_ref_price_tier)),
+            ("etp_flag", name(dir.etp_flag)),
+            ("etp_leverage_factor", uint(dir.etp_leverage_factor)),
+            ("inverse_indicator", dir.inverse_indicator.into()),
+        ],
+        Body::RegShoRestriction(reg_sho) => vec![
+            ("stock", text(alpha(&reg_sho.stock))),
+            ("reg_sho_action", name(reg_sho.reg_sho_action)),
+        ],
+        Body::MarketParticipantPosition(position) => vec![
+            ("stock", text(alpha(&position.stock))),
+            ("mpid", text(alpha(&position.mpid))),
+            ("primary_market_maker", position.primary_market_maker.into()),
+            ("market_maker_mode", name(position.market_maker_mode)),
+            (
+                "market_participant_state",
+                name(position.market_participant_state),
+            ),
+        ],
+        Body::MwcbDeclineLevel(levels) => vec![
+            ("mwcb_level_1", levels.level1.into()),
+            ("mwcb_level_2", levels.level2.into()),
+            ("mwcb_level_3", levels.level3.into()),
+        ],
+        Body::MwcbStatus(status) => vec![("breached_level", name(status.breached_level))],
+        Body::IpoQuotingPeriodUpdate(ipo) => vec![
+            ("stock", text(alpha(&ipo.stock))),
+            ("release_time", ipo.release_time.0.into()),
+            ("release_qualifier", name(ipo.release_qualifier)),
+            ("ipo_price", ipo.ipo_price.into()),
+        ],
+        Body::LuldAuctionCollar(collar) => vec![
+            ("stock", text(alpha(&collar.stock))),
+            ("reference_price", collar.reference_price.into()),
+            ("upper_price", collar.upper_price.into()),
+            ("lower_price", collar.lower_price.into()),
+            ("extension", uint(collar.extension)),
+        ],
+        Body::OperationalHalt(halt) => vec![
+            ("stock", text(alpha(&halt.stock))),
+            ("market_code", name(halt.market_code)),
+            ("halt_action", name(halt.action)),
+        ],
+        Body::DirectListingPriceDiscovery(discovery) => vec![
+            ("stock", text(alpha(&discovery.stock))),
+            ("open_eligible", discovery.open_eligible.into()),
+            ("min_allowable_price", discovery.min_allowable_price.into()),
+            ("max_allowable_price", discovery.max_allowable_price.into()),
+            (
+                "near_execution_price",
+                discovery.near_execution_price.into(),
+            ),
+            (
+                "near_execution_time",
+                discovery.near_execution_time.0.into(),
+            ),
+            (
+                "lower_price_range_collar",
+                discovery.lower_price_range_collar.into(),
+            ),
+            (
+                "upper_price_range_collar",
+                discovery.upper_price_range_collar.into(),
+            ),
+        ],
+        Body::RetailPriceImprovement(rpi) => vec![
+            ("stock", text(alpha(&rpi.stock))),
+            ("interest", name(rpi.interest)),
+        ],
+        Body::SystemEvent(event) => vec![("event_code", name(event.event_code))],
+        Body::Unknown { bytes, .. } => vec![(
+            "raw",
+            text(bytes.iter().map(|byte| format!("{:02x}", byte)).collect()),
+        )],
+    }
+}
+
+// Writes the snapshots, trades and events tables of a replay into a
+// directory. Books are sampled whenever the ITCH time crosses a multiple of
+// the interval and once more at the end of the replay.
+pub struct Exporter {
+    snapshots: Box<dyn Table>,
+    trades: Box<dyn Table>,
+    events: Box<dyn Table>,
+    event_columns: HashMap<&'static str, usize>,
+    book_depth: usize,
+    interval: u64,
+    next_snapshot: Option<Timestamp>,
+}
+
+impl Exporter {
+    pub fn new(dir: &Path, format: Format, book_depth: usize, interval: u64) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let path = |name: &str| dir.join(format!("{}.{}", name, format.extension()));
+        Ok(Exporter {
+            snapshots: create_table(
+                &path("snapshots"),
+                format,
+                "snapshots",
+                snapshot_columns(book_depth),
+            )?,
+            trades: create_table(&path("trades"), format, "trades", columns(&TRADE_COLUMNS))?,
+            events: create_table(&path("events"), format, "events", columns(&EVENT_COLUMNS))?,
+            event_columns: EVENT_COLUMNS
+                .iter()
+                .enumerate()
+                .map(|(index, (name, _))| (*name, index))
+                .collect(),
+            book_depth,
+            interval: interval.max(1),
+            next_snapshot: None,
+        })
+    }
+
+    // Called before each message is processed, so that snapshots hold the
+    // books as they were when the interval ended.
+    pub fn sample(&mut self, pipeline: &Pipeline, timestamp: Timestamp) -> io::Result<()> {
+        let next = Timestamp((timestamp.0 / self.interval + 1) * self.interval);
+        match self.next_snapshot {
+            Some(snapshot) if timestamp >= snapshot => {
+                self.write_snapshots(pipeline)?;
+                self.next_snapshot = Some(next);
+            }
+            Some(_) => (),
+            None => self.next_snapshot = Some(next),
+        }
+        Ok(())
+    }
+
+    fn write_snapshots(&mut self, pipeline: &Pipeline) -> io::Result<()> {
+        for stock_locate in pipeline.selected_stock_locates() {
+            let state = pipeline.state_manager.state(stock_locate);
+            let mut row: Vec<Value> = vec![
+                pipeline
+                    .symbol(stock_locate)
+                    .map(|stock| alpha(&stock))
+                    .into(),
+                (stock_locate as u64).into(),
+                pipeline.sequence_number.into(),
+                pipeline.timestamp.0.into(),
+                pipeline.capture_timestamp.into(),
+                state
+                    .and_then(|state| state.trading_state)
+                    .map(|trading_state| format!("{:?}", trading_state))
+                    .into(),
+            ];
+            let bids = pipeline
+                .book_manager
+                .levels(stock_locate, Side::Buy, self.book_depth);
+            let asks = pipeline
+                .book_manager
+                .levels(stock_locate, Side::Sell, self.book_depth);
+            for level in 0..self.book_depth {
+                for levels in [&bids, &asks] {
+                    match levels.get(level) {
+                        Some(level) => row.extend([
+                            level.price.into(),
+                            (level.shares as u64).into(),
+                            (level.orders as u64).into(),
+                        ]),
+                        None => row.extend([Value::Null, Value::Null, Value::Null]),
+                    }
+                }
+            }
+            self.snapshots.write_row(&row)?;
+        }
+        Ok(())
+    }
+
+    // Called with each message processed by the pipeline.
+    pub fn event(
+        &mut self,
+        pipeline: &Pipeline,
+        view: &MessageView,
+        message: &Message,
+    ) -> io::Result<()> {
+        if !pipeline.is_selected(view.stock_locate()) {
+            return Ok(());
+        }
+        let mut row = vec![Value::Null; EVENT_COLUMNS.len()];
+        row[..EVENT_HEADER_COLUMNS].clone_from_slice(&[
+            pipeline.sequence_number.into(),
+            pipeline.capture_timestamp.into(),
+            view.timestamp().0.into(),
+            String::from(view.msg_type() as char).into(),
+            (view.stock_locate() as u64).into(),
+            (view.tracking_number() as u64).into(),
+        ]);
+        for (column, value) in event_fields(&message.body) {
+            row[self.event_columns[column]] = value;
+        }
+        self.events.write_row(&row)
+    }
+
+    // Writes the last snapshot and the trade tape, which is only final once
+    // broken trades have been removed.
+    pub fn finish(mut self, pipeline: &Pipeline) -> io::Result<()> {
+        self.write_snapshots(pipeline)?;
+        for stock_locate in pipeline.selected_stock_locates() {
+            let symbol = pipeline.symbol(stock_locate).map(|stock| alpha(&stock));
+            for trade in pipeline.trade_manager.trades(stock_locate) {
+                self.trades.write_row(&[
+                    symbol.clone().into(),
+                    (stock_locate as u64).into(),
+                    trade.timestamp.0.into(),
+                    trade.price.into(),
+                    trade.shares.into(),
+                    trade.match_number.into(),
+                    trade.printable.into(),
+                    trade
+                        .cross_type
+                        .map(|cross_type| format!("{:?}", cross_type))
+                        .into(),
+                    trade.capture_timestamp.into(),
+                ])?;
+            }
+        }
+        self.snapshots.finish()?;
+        self.trades.finish()?;
+        self.events.finish()
+    }
+}
@@ -0,0 +1,415 @@
+use crate::enums::{
+    Authenticity, EtpFlag, FinancialStatus, IpoFlag, IssueClassification, IssueSubType,
+    LuldRefPriceTier, MarketCategory, Side, ThresholdIndicator,
+};
+use crate::messages::{
+    AddOrder, Body, CancelOrder, DeleteOrder, ExecutedOrder, Message, Mpid, ReplaceOrder, Stock,
+    StockDirectory,
+};
+use crate::price::Price4;
+use crate::timestamp::{Timestamp, TradeDate};
+
+const MPIDS: [&str; 4] = ["NITE", "GSCO", "MSCO", "CDRG"];
+
+// Relative weights of the order events generated once a book has live orders.
+#[derive(Debug, Clone, Copy)]
+pub struct EventMix {
+    pub add: u32,
+    pub execute: u32,
+    pub cancel: u32,
+    pub replace: u32,
+    pub delete: u32,
+}
+
+impl Default for EventMix {
+    fn default() -> Self {
+        EventMix {
+            add: 45,
+            execute: 15,
+            cancel: 15,
+            replace: 10,
+            delete: 15,
+        }
+    }
+}
+
+// How the mid price moves between events, in steps of up to volatility ticks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PriceProcess {
+    RandomWalk,
+    // Each event also pulls the mid back by this fraction of its distance to
+    // the initial price.
+    MeanReverting { strength: f64 },
+    // A random walk whose mid jumps by up to size ticks, either way, with the
+    // given probability per event.
+    Jump { probability: f64, size: u32 },
+}
+
+#[derive(Debug, Clone)]
+pub struct GeneratorConfig {
+    pub symbols: Vec<String>,
+    pub message_count: u64,
+    pub rate: u64,
+    pub start_time: Timestamp,
+    pub initial_price: u32,
+    pub tick_size: u32,
+    pub volatility: u32,
+    pub price_process: PriceProcess,
+    pub mix: EventMix,
+    pub seed: u64,
+    // Date the capture timestamps of written pcaps fall on.
+    pub trade_date: TradeDate,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        GeneratorConfig {
+            symbols: vec![String::from("AAPL")],
+            message_count: 10000,
+            rate: 100000,
+            start_time: Timestamp(34200 * 1_000_000_000),
+            initial_price: 1000000,
+            tick_size: 100,
+            volatility: 1,
+            price_process: PriceProcess::RandomWalk,
+            mix: EventMix::default(),
+            seed: 1,
+            trade_date: TradeDate::new(2023, 11, 21).unwrap(),
+        }
+    }
+}
+
+// SplitMix64, good enough for test data and keeps generated files reproducible.
+#[derive(Debug)]
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+
+    fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct LiveOrder {
+    reference: u64,
+    side: Side,
+    price: u32,
+    shares: u32,
+}
+
+#[derive(Debug)]
+struct Instrument {
+    stock_locate: u16,
+    stock: Stock,
+    mid: u32,
+    orders: Vec<LiveOrder>,
+}
+
+#[derive(Debug)]
+pub struct Generator {
+    config: GeneratorConfig,
+    rng: Rng,
+    instruments: Vec<Instrument>,
+    timestamp: Timestamp,
+    next_reference: u64,
+    next_match_number: u64,
+    directory_sent: usize,
+    generated: u64,
+}
+
+impl Generator {
+    pub fn new(config: GeneratorConfig) -> Self {
+        let instruments = config
+            .symbols
+            .iter()
+            .enumerate()
+            .map(|(index, symbol)| Instrument {
+                stock_locate: index as u16 + 1,
+                stock: Stock::from(symbol.to_uppercase().as_str()),
+                mid: config.initial_price,
+                orders: Vec::new(),
+            })
+            .collect();
+        Generator {
+            rng: Rng(config.seed),
+            timestamp: config.start_time,
+            instruments,
+            config,
+            next_reference: 1,
+            next_match_number: 1,
+            directory_sent: 0,
+            generated: 0,
+        }
+    }
+
+    fn directory(&self, instrument: &Instrument) -> Body {
+        Body::StockDirectory(StockDirectory {
+            stock_locate: instrument.stock_locate,
+            tracking_number: 0,
+            timestamp: self.timestamp,
+            stock: instrument.stock,
+            market_category: MarketCategory::NasdaqGlobalSelect,
+            financial_status: FinancialStatus::Normal,
+            round_lot_size: 100,
+            round_lots_only: false,
+            issue_classification: IssueClassification::CommonStock,
+            issue_subtype: IssueSubType::CommonShares,
+            authenticity: Authenticity::Test,
+            short_sale_threshold: ThresholdIndicator::NotRestricted,
+            ipo_flag: IpoFlag::NotNew,
+            luld_ref_price_tier: LuldRefPriceTier::Tier1,
+            etp_flag: EtpFlag::IsNot,
+            etp_leverage_factor: 0,
+            inverse_indicator: false,
+        })
+    }
+
+    fn advance_clock(&mut self) {
+        let mean = 1_000_000_000.0 / self.config.rate.max(1) as f64;
+        let gap = -(1.0 - self.rng.unit()).ln() * mean;
+        self.timestamp.0 += gap.max(1.0) as u64;
+    }
+
+    // Quotes never lock or cross the opposite side so the book stays realistic.
+    fn quote_price(&mut self, index: usize, side: Side) -> u32 {
+        let tick_size = self.config.tick_size;
+        let offset = tick_size * (1 + self.rng.below(10) as u32);
+        let instrument = &self.instruments[index];
+        let opposite = instrument.orders.iter().filter(|o| o.side != side);
+        match side {
+            Side::Buy => {
+                let best_ask = opposite.map(|o| o.price).min().unwrap_or(u32::MAX);
+                instrument
+                    .mid
+                    .saturating_sub(offset)
+                    .min(best_ask.saturating_sub(tick_size))
+                    .max(tick_size)
+            }
+            Side::Sell => {
+                let best_bid = opposite.map(|o| o.price).max().unwrap_or(0);
+                (instrument.mid + offset).max(best_bid + tick_size)
+            }
+        }
+    }
+
+    fn move_mid(&mut self, index: usize) {
+        let volatility = self.config.volatility as u64;
+        let tick_size = self.config.tick_size as i64;
+        let mut step = self.rng.below(2 * volatility + 1) as i64 - volatility as i64;
+        let mid = self.instruments[index].mid as i64;
+        match self.config.price_process {
+            PriceProcess::RandomWalk => (),
+            PriceProcess::MeanReverting { strength } => {
+                // Pull a fraction of the distance back in ticks, rounding at
+                // random so small distances still pull on average.
+                let distance = (self.config.initial_price as i64 - mid) / tick_size;
+                let pull = distance as f64 * strength;
+                step += pull.trunc() as i64;
+                if self.rng.unit() < pull.fract().abs() {
+                    step += pull.signum() as i64;
+                }
+            }
+            PriceProcess::Jump { probability, size } => {
+                if self.rng.unit() < probability {
+                    let size = size.max(1) as u64;
+                    step += self.rng.below(2 * size + 1) as i64 - size as i64;
+                }
+            }
+        }
+        let instrument = &mut self.instruments[index];
+        let mid = mid + step * tick_size;
+        let mut mid = mid.max(tick_size * 20) as u32;
+
+        // Keep the mid inside the spread so quotes do not pile up at the touch.
+        let bids = instrument.orders.iter().filter(|o| o.side == Side::Buy);
+        let asks = instrument.orders.iter().filter(|o| o.side == Side::Sell);
+        if let Some(best_bid) = bids.map(|o| o.price).max() {
+            mid = mid.max(best_bid);
+        }
+        if let Some(best_ask) = asks.map(|o| o.price).min() {
+            mid = mid.min(best_ask);
+        }
+        instrument.mid = mid;
+    }
+
+    fn add_order(&mut self, index: usize) -> Body {
+        let side = if self.rng.below(2) == 0 {
+            Side::Buy
+        } else {
+            Side::Sell
+        };
+        let price = self.quote_price(index, side);
+        let shares = 100 * (1 + self.rng.below(10) as u32);
+        let attribution = match self.rng.below(5) {
+            0 => Some(Mpid::from(
+                MPIDS[self.rng.below(MPIDS.len() as u64) as usize],
+            )),
+            _ => None,
+        };
+        let reference = self.next_reference;
+        self.next_reference += 1;
+
+        let instrument = &mut self.instruments[index];
+        instrument.orders.push(LiveOrder {
+            reference,
+            side,
+            price,
+            shares,
+        });
+        Body::AddOrder(AddOrder {
+            stock_locate: instrument.stock_locate,
+            tracking_number: 0,
+            timestamp: self.timestamp,
+            reference,
+            side,
+            shares,
+            stock: instrument.stock,
+            price: Price4(price),
+            attribution,
+        })
+    }
+
+    fn execute_order(&mut self, index: usize, position: usize) -> Body {
+        let order = self.instruments[index].orders[position];
+        let executed_shares = 1 + self.rng.below(order.shares as u64) as u32;
+        let match_number = self.next_match_number;
+        self.next_match_number += 1;
+
+        let instrument = &mut self.instruments[index];
+        if executed_shares == order.shares {
+            instrument.orders.swap_remove(position);
+        } else {
+            instrument.orders[position].shares -= executed_shares;
+        }
+        Body::ExecutedOrder(ExecutedOrder {
+            stock_locate: instrument.stock_locate,
+            tracking_number: 0,
+            timestamp: self.timestamp,
+            reference: order.reference,
+            executed_shares,
+            match_number,
+        })
+    }
+
+    fn cancel_order(&mut self, index: usize, position: usize) -> Body {
+        let order = self.instruments[index].orders[position];
+        if order.shares == 1 {
+            return self.delete_order(index, position);
+        }
+        let canceled_shares = 1 + self.rng.below(order.shares as u64 - 1) as u32;
+
+        let instrument = &mut self.instruments[index];
+        instrument.orders[position].shares -= canceled_shares;
+        Body::CancelOrder(CancelOrder {
+            stock_locate: instrument.stock_locate,
+            tracking_number: 0,
+            timestamp: self.timestamp,
+            reference: order.reference,
+            canceled_shares,
+        })
+    }
+
+    fn replace_order(&mut self, index: usize, position: usize) -> Body {
+        let order = self.instruments[index].orders[position];
+        let price = self.quote_price(index, order.side);
+        let shares = 100 * (1 + self.rng.below(10) as u32);
+        let new_reference = self.next_reference;
+        self.next_reference += 1;
+
+        let instrument = &mut self.instruments[index];
+        instrument.orders[position] = LiveOrder {
+            reference: new_reference,
+            side: order.side,
+            price,
+            shares,
+        };
+        Body::ReplaceOrder(ReplaceOrder {
+            stock_locate: instrument.stock_locate,
+            tracking_number: 0,
+            timestamp: self.timestamp,
+            original_reference: order.reference,
+            new_reference,
+            price: Price4(price),
+            shares,
+        })
+    }
+
+    fn delete_order(&mut self, index: usize, position: usize) -> Body {
+        let instrument = &mut self.instruments[index];
+        let order = instrument.orders.swap_remove(position);
+        Body::DeleteOrder(DeleteOrder {
+            stock_locate: instrument.stock_locate,
+            tracking_number: 0,
+            timestamp: self.timestamp,
+            reference: order.reference,
+        })
+    }
+
+    // Executions hit the top of the book, on the side of the randomly picked order.
+    fn best_order(&self, index: usize, position: usize) -> usize {
+        let orders = &self.instruments[index].orders;
+        let side = orders[position].side;
+        let candidates = orders.iter().enumerate().filter(|(_, o)| o.side == side);
+        match side {
+            Side::Buy => candidates.max_by_key(|(_, o)| o.price),
+            Side::Sell => candidates.min_by_key(|(_, o)| o.price),
+        }
+        .map_or(position, |(best, _)| best)
+    }
+
+    fn next_event(&mut self) -> Body {
+        self.advance_clock();
+        let index = self.rng.below(self.instruments.len() as u64) as usize;
+        self.move_mid(index);
+
+        let live = self.instruments[index].orders.len();
+        if live == 0 {
+            return self.add_order(index);
+        }
+        let position = self.rng.below(live as u64) as usize;
+        let mix = self.config.mix;
+        let total = mix.add + mix.execute + mix.cancel + mix.replace + mix.delete;
+        let roll = self.rng.below(total.max(1) as u64) as u32;
+        if roll < mix.add {
+            self.add_order(index)
+        } else if roll < mix.add + mix.execute {
+            let position = self.best_order(index, position);
+            self.execute_order(index, position)
+        } else if roll < mix.add + mix.execute + mix.cancel {
+            self.cancel_order(index, position)
+        } else if roll < mix.add + mix.execute + mix.cancel + mix.replace {
+            self.replace_order(index, position)
+        } else {
+            self.delete_order(index, position)
+        }
+    }
+}
+
+impl Iterator for Generator {
+    type Item = Message;
+
+    fn next(&mut self) -> Option<Message> {
+        if self.directory_sent < self.instruments.len() {
+            let body = self.directory(&self.instruments[self.directory_sent]);
+            self.directory_sent += 1;
+            return Some(Message::with_body(body));
+        }
+        if self.instruments.is_empty() || self.generated >= self.config.message_count {
+            return None;
+        }
+        self.generated += 1;
+        Some(Message::with_body(self.next_event()))
+    }
+}
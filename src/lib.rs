@@ -0,0 +1,17 @@
+pub mod auctionmanager;
+pub mod bookmanager;
+pub mod enums;
+pub mod error;
+pub mod export;
+pub mod generator;
+pub mod messages;
+pub mod parallel;
+pub mod pipeline;
+pub mod price;
+pub mod reader;
+pub mod report;
+pub mod statemanager;
+pub mod timestamp;
+pub mod trademanager;
+pub mod utils;
+pub mod writer;
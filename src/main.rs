@@ -1,21 +1,30 @@
-use clap::{value_parser, Arg, Command};
-use pcap::Capture;
-use std::collections::HashMap;
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use std::fmt;
+use std::io::{self, BufWriter, Write};
 use std::path::Path;
+use std::process;
+use std::thread;
 
-use std::mem;
-
-mod bookmanager;
-mod enums;
-mod messages;
-mod utils;
+use bookbuilder::enums::Side;
+use bookbuilder::export::{Exporter, Format};
+use bookbuilder::messages::MessageView;
+use bookbuilder::parallel;
+use bookbuilder::pipeline::Pipeline;
+use bookbuilder::reader::{self, StopCondition};
+use bookbuilder::report::{
+    BboChange, Indicative, LevelUpdate, MarketReport, Participants, Report, Snapshot,
+};
+use bookbuilder::timestamp::{Timestamp, TradeDate, UtcTime};
 
-use bookmanager::{BookManager, Order, OrderManager};
+#[cfg(feature = "parquet")]
+const EXPORT_FORMATS: [&str; 2] = ["csv", "parquet"];
+#[cfg(not(feature = "parquet"))]
+const EXPORT_FORMATS: [&str; 1] = ["csv"];
 
-use messages::{Body, Message, PacketHeader};
-use utils::{
-    as_u16, PACKET_HEADER_LENGTH, PACKET_HEADER_OFFSET, UDP_DEST_PORT, UDP_DEST_PORT_OFFSET,
-};
+fn exit_with(error: impl fmt::Display) -> ! {
+    eprintln!("{}", error);
+    process::exit(2);
+}
 
 fn main() {
     let matches = Command::new("bookbuilder")
@@ -23,11 +32,20 @@ fn main() {
             Arg::new("file")
                 .short('f')
                 .long("file")
-                .value_name("PCAP FILE")
-                .help("Sets a pcap file path as input.")
+                .value_name("FILE")
+                .help("Sets a pcap or raw ITCH file path as input.")
                 .num_args(1)
                 .required(true),
         )
+        .arg(
+            Arg::new("reader")
+                .long("reader")
+                .value_name("READER")
+                .help("Sets how the input is read: memory-mapped, or streamed through libpcap and buffered reads.")
+                .value_parser(["mmap", "libpcap"])
+                .num_args(1)
+                .default_value("mmap"),
+        )
         .arg(
             Arg::new("symbol")
                 .short('s')
@@ -35,7 +53,24 @@ fn main() {
                 .value_name("SYMBOL")
                 .help("Sets stock symbol.")
                 .num_args(1)
-                .required(true),
+                .required_unless_present("all"),
+        )
+        .arg(
+            Arg::new("all")
+                .long("all")
+                .help("Replays every symbol, sharded by stock locate across threads.")
+                .conflicts_with("symbol")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("threads")
+                .short('j')
+                .long("threads")
+                .value_name("THREADS")
+                .help("Sets the number of worker threads of --all replays.")
+                .value_parser(value_parser!(usize))
+                .num_args(1)
+                .requires("all"),
         )
         .arg(
             Arg::new("book_depth")
@@ -52,58 +87,388 @@ fn main() {
                 .short('n')
                 .long("sequence-number")
                 .value_name("SEQNUM")
-                .help("Sets sequence number to stop.")
+                .help("Sets sequence number to stop. Defaults to the end of the capture.")
+                .value_parser(value_parser!(u64))
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("capture_time")
+                .short('c')
+                .long("capture-time")
+                .value_name("TIME")
+                .help("Sets capture time to stop, in seconds since the epoch or as HH:MM:SS.nnnnnnnnn Eastern time on the trade date.")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("date")
+                .long("date")
+                .value_name("DATE")
+                .help("Sets the trade date as YYYY-MM-DD, read from the file name by default.")
+                .value_parser(|value: &str| value.parse::<TradeDate>())
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("participants")
+                .short('p')
+                .long("participants")
+                .help("Displays registered market participants quoting the book.")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("events")
+                .short('e')
+                .long("events")
+                .help("Displays market-wide events such as circuit breaker breaches.")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("directory")
+                .short('r')
+                .long("directory")
+                .help("Displays the stock directory of every listed symbol.")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("auction")
+                .short('a')
+                .long("auction")
+                .help("Displays the imbalance evolution and final cross of each auction.")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("indicative")
+                .short('i')
+                .long("indicative")
+                .help("Displays the auction price computed by crossing the current book.")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("format")
+                .short('o')
+                .long("format")
+                .value_name("FORMAT")
+                .help("Sets the output format of the book and reports, json prints one object per line.")
+                .value_parser(["text", "json"])
+                .num_args(1)
+                .default_value("text"),
+        )
+        .arg(
+            Arg::new("export")
+                .short('x')
+                .long("export")
+                .value_name("DIR")
+                .help("Exports book snapshots, trades and decoded messages as tables into a directory instead of displaying the book.")
+                .num_args(1)
+                .conflicts_with("threads"),
+        )
+        .arg(
+            Arg::new("export_format")
+                .long("export-format")
+                .value_name("FORMAT")
+                .help("Sets the format of exported tables.")
+                .value_parser(EXPORT_FORMATS)
+                .num_args(1)
+                .default_value("csv")
+                .requires("export"),
+        )
+        .arg(
+            Arg::new("interval")
+                .long("interval")
+                .value_name("MS")
+                .help("Sets the interval between exported book snapshots, in milliseconds of ITCH time.")
                 .value_parser(value_parser!(u64))
                 .num_args(1)
-                .required(true),
+                .default_value("1000")
+                .requires("export"),
+        )
+        .arg(
+            Arg::new("trades")
+                .short('t')
+                .long("trades")
+                .value_name("FORMAT")
+                .help("Exports the trade tape instead of the book, json prints one object per line.")
+                .value_parser(["csv", "json"])
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("bbo")
+                .short('b')
+                .long("bbo")
+                .value_name("FORMAT")
+                .help("Streams every change of the best bid or offer instead of displaying the book, json prints one object per line.")
+                .value_parser(["csv", "json"])
+                .num_args(1)
+                .conflicts_with_all(["threads", "export", "trades"]),
+        )
+        .arg(
+            Arg::new("deltas")
+                .long("deltas")
+                .value_name("FORMAT")
+                .help("Streams every book level update instead of displaying the book, as 48 byte binary records or one json object per line.")
+                .value_parser(["binary", "json"])
+                .num_args(1)
+                .conflicts_with_all(["threads", "export", "trades", "bbo"]),
         )
         .get_matches();
 
     let file = matches.get_one::<String>("file").unwrap();
-    let symbol = format!("{:8}", matches.get_one::<String>("symbol").unwrap()).to_uppercase();
-    let depth = matches.get_one::<u32>("book_depth").unwrap();
-    let seqnum = matches.get_one::<u64>("sequence_number").unwrap();
-    let mut listing = HashMap::<u16, String>::new();
-    let mut order_manager = OrderManager::new();
-    let mut book_manager = BookManager::new();
-
-    let mut capture = Capture::from_file(Path::new(file)).unwrap();
-
-    while let Ok(packet) = capture.next_packet() {
-        let udp_dest_port = as_u16(&packet.data[UDP_DEST_PORT_OFFSET..UDP_DEST_PORT_OFFSET + 2]);
-        if udp_dest_port == UDP_DEST_PORT {
-            let packet_header = PacketHeader::new(&packet.data[PACKET_HEADER_OFFSET..]);
-            if packet_header.sequence_number > *seqnum {
-                break;
+    let mut stop = StopCondition::default();
+    if let Some(seqnum) = matches.get_one::<u64>("sequence_number") {
+        stop.sequence_number = *seqnum;
+    }
+    if let Some(capture_time) = matches.get_one::<String>("capture_time") {
+        let date = matches
+            .get_one::<TradeDate>("date")
+            .copied()
+            .or_else(|| TradeDate::from_path(Path::new(file)));
+        let capture_time = match (capture_time.contains(':'), date) {
+            (true, Some(date)) => capture_time
+                .parse::<Timestamp>()
+                .map(|timestamp| timestamp.to_utc(date)),
+            (true, None) => exit_with("--date is needed to stop at an Eastern time"),
+            (false, _) => UtcTime::from_epoch(capture_time),
+        };
+        stop.capture_timestamp = capture_time.unwrap_or_else(|error| exit_with(error)).0;
+    }
+    let open = match matches.get_one::<String>("reader").unwrap().as_str() {
+        "libpcap" => reader::open_libpcap,
+        _ => reader::open,
+    };
+    let mut reader = open(Path::new(file)).unwrap_or_else(|error| exit_with(error));
+
+    let export = matches.get_one::<String>("export");
+    let bbo = matches
+        .get_one::<String>("bbo")
+        .map(|format| format.as_str());
+    let deltas = matches
+        .get_one::<String>("deltas")
+        .map(|format| format.as_str());
+    let streaming = bbo.is_some() || deltas.is_some();
+    if matches.get_flag("all") && export.is_none() && !streaming {
+        let threads = matches
+            .get_one::<usize>("threads")
+            .copied()
+            .unwrap_or_else(|| thread::available_parallelism().map_or(1, |threads| threads.get()));
+        let pipeline = parallel::replay_sharded(reader.as_mut(), stop, threads);
+        display_market(&pipeline, &matches);
+
+        let mut listing: Vec<_> = pipeline.listing.iter().collect();
+        listing.sort();
+        for (sl, stock) in listing {
+            display_symbol(&pipeline, *sl, &stock.to_string(), &matches);
+        }
+        return;
+    }
+
+    let symbol = matches
+        .get_one::<String>("symbol")
+        .map(|symbol| format!("{:8}", symbol).to_uppercase());
+    let mut pipeline = match &symbol {
+        Some(symbol) => Pipeline::with_symbols(&[symbol]),
+        None => Pipeline::new(),
+    };
+    let mut exporter = export.map(|dir| {
+        let format = match matches.get_one::<String>("export_format").unwrap().as_str() {
+            #[cfg(feature = "parquet")]
+            "parquet" => Format::Parquet,
+            _ => Format::Csv,
+        };
+        Exporter::new(
+            Path::new(dir),
+            format,
+            *matches.get_one::<u32>("book_depth").unwrap() as usize,
+            matches.get_one::<u64>("interval").unwrap() * 1_000_000,
+        )
+        .unwrap_or_else(|error| exit_with(error))
+    });
+    if bbo.is_some() {
+        pipeline.book_manager.track_bbo();
+    }
+    if deltas.is_some() {
+        pipeline.book_manager.track_deltas();
+    }
+    let mut out = BufWriter::new(io::stdout());
+    if bbo == Some("csv") {
+        writeln!(out, "{}", BboChange::CSV_HEADER).unwrap_or_else(|error| exit_with(error));
+    }
+
+    while let Some(packet) = reader.next_packet() {
+        if stop.is_reached(&packet) {
+            break;
+        }
+        pipeline.capture_timestamp = packet.timestamp;
+
+        for (index, bytes) in packet.messages().enumerate() {
+            pipeline.sequence_number = packet.sequence_number + index as u64;
+            let view = match MessageView::new(bytes) {
+                Ok(view) => view,
+                Err(error) => {
+                    eprintln!("[{}] {}", packet.sequence_number, error);
+                    continue;
+                }
+            };
+            if let Some(exporter) = exporter.as_mut() {
+                exporter
+                    .sample(&pipeline, view.timestamp())
+                    .unwrap_or_else(|error| exit_with(error));
             }
-            let mut offset = PACKET_HEADER_OFFSET + PACKET_HEADER_LENGTH;
-
-            //println!("------------------");
-            //println!("{:?}", packet.data);
-            let mut msg_count = packet_header.message_count;
-            //println!("[{}] count: {}", packet_header.sequence_number, msg_count);
-
-            while msg_count > 0 {
-                let msg = Message::new(&packet.data[offset..]);
-                //println!("{:?}", &msg);
-                if msg.body != Body::None {
-                    let order = Order::new(&msg, &mut listing);
-                    order_manager.process(&order, &mut book_manager);
-                    //println!("{:?}", book_manager);
+            match pipeline.process_view(&view) {
+                Ok(true) => {
+                    if let Some(exporter) = exporter.as_mut() {
+                        match view.decode() {
+                            Ok(message) => exporter
+                                .event(&pipeline, &view, &message)
+                                .unwrap_or_else(|error| exit_with(error)),
+                            Err(error) => eprintln!("[{}] {}", packet.sequence_number, error),
+                        }
+                    }
+                    if let Some(format) = bbo {
+                        write_bbo_changes(&mut pipeline, format, &mut out)
+                            .unwrap_or_else(|error| exit_with(error));
+                    }
+                    if let Some(format) = deltas {
+                        write_deltas(&mut pipeline, format, &mut out)
+                            .unwrap_or_else(|error| exit_with(error));
+                    }
                 }
-                msg_count -= 1;
-                offset += msg.length as usize + mem::size_of_val(&msg.length);
+                Ok(false) => (),
+                Err(error) => eprintln!("[{}] {}", packet.sequence_number, error),
             }
         }
     }
 
-    let associate_stock_locate =
-        listing
-            .iter()
-            .find_map(|(key, val)| if *val == *symbol { Some(*key) } else { None });
+    if let Some(exporter) = exporter {
+        return exporter
+            .finish(&pipeline)
+            .unwrap_or_else(|error| exit_with(error));
+    }
+    if streaming {
+        return out.flush().unwrap_or_else(|error| exit_with(error));
+    }
+
+    display_market(&pipeline, &matches);
+    let symbol = symbol.unwrap();
+    if let Some(sl) = pipeline.stock_locate(&symbol) {
+        display_symbol(&pipeline, sl, &symbol, &matches);
+    }
+}
 
-    if let Some(sl) = associate_stock_locate {
-        println!("Book (depth: {}) : {}", depth, symbol);
-        book_manager.display_book(sl, *depth as usize);
+fn write_bbo_changes(
+    pipeline: &mut Pipeline,
+    format: &str,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    for (sl, bbo) in pipeline.book_manager.bbo_changes() {
+        if !pipeline.is_selected(sl) {
+            continue;
+        }
+        let change = BboChange::new(pipeline, sl, bbo);
+        match format {
+            "json" => writeln!(out, "{}", change.to_json())?,
+            _ => writeln!(out, "{}", change.to_csv())?,
+        }
+    }
+    Ok(())
+}
+
+fn write_deltas(pipeline: &mut Pipeline, format: &str, out: &mut impl Write) -> io::Result<()> {
+    let mut bytes = Vec::with_capacity(LevelUpdate::LENGTH);
+    for (sl, delta) in pipeline.book_manager.take_deltas() {
+        if !pipeline.is_selected(sl) {
+            continue;
+        }
+        let update = LevelUpdate::new(pipeline, sl, delta);
+        match format {
+            "json" => writeln!(out, "{}", update.to_json())?,
+            _ => {
+                bytes.clear();
+                update.encode(&mut bytes);
+                out.write_all(&bytes)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_json(matches: &ArgMatches) -> bool {
+    matches.get_one::<String>("format").unwrap() == "json"
+}
+
+fn display_market(pipeline: &Pipeline, matches: &ArgMatches) {
+    if is_json(matches) {
+        if matches.get_flag("directory") || matches.get_flag("events") {
+            let mut report = MarketReport::new(pipeline);
+            if matches.get_flag("directory") {
+                report.directory = Some(pipeline.state_manager.directory());
+            }
+            if matches.get_flag("events") {
+                report.events = Some(pipeline.state_manager.events());
+            }
+            println!("{}", Report::Market(report).to_json());
+        }
+        return;
+    }
+
+    if matches.get_flag("directory") {
+        pipeline.state_manager.display_directory();
+    }
+
+    if matches.get_flag("events") {
+        pipeline.state_manager.display_events();
+    }
+}
+
+fn display_symbol(pipeline: &Pipeline, sl: u16, symbol: &str, matches: &ArgMatches) {
+    let depth = matches.get_one::<u32>("book_depth").unwrap();
+    let trades = matches.get_one::<String>("trades");
+    let Pipeline {
+        order_manager,
+        book_manager,
+        state_manager,
+        trade_manager,
+        auction_manager,
+        ..
+    } = pipeline;
+
+    match trades.map(|format| format.as_str()) {
+        Some("csv") => return trade_manager.display_trades_csv(sl),
+        Some("json") => return trade_manager.display_trades_json(sl),
+        _ => (),
+    }
+
+    if is_json(matches) {
+        let mut snapshot = Snapshot::new(pipeline, sl, *depth as usize);
+        if matches.get_flag("participants") {
+            snapshot.participants = Some(Participants::new(pipeline, sl, *depth as usize));
+        }
+        if matches.get_flag("auction") {
+            snapshot.auctions = Some(auction_manager.auctions(sl));
+        }
+        if matches.get_flag("indicative") {
+            snapshot.indicative = Some(Indicative::new(pipeline, sl));
+        }
+        println!("{}", Report::Snapshot(snapshot).to_json());
+        return;
+    }
+
+    println!("Book (depth: {}) : {}", depth, symbol);
+    state_manager.display_state(sl);
+    book_manager.display_book(sl, *depth as usize);
+    if matches.get_flag("participants") {
+        let quoting = |side| {
+            state_manager.quoting(
+                sl,
+                &book_manager.levels(sl, side, *depth as usize),
+                &order_manager.attributions(sl, side),
+            )
+        };
+        state_manager.display_participants(sl, &quoting(Side::Buy), &quoting(Side::Sell));
+    }
+    if matches.get_flag("auction") {
+        auction_manager.display_auctions(sl);
+    }
+    if matches.get_flag("indicative") {
+        book_manager.display_indicative_auction(sl, pipeline.reference_price(sl));
+        auction_manager.display_latest_imbalance(sl);
     }
 }
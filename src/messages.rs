@@ -1,8 +1,115 @@
+use std::fmt;
+use std::str;
+
+use serde::{Serialize, Serializer};
+
 use crate::enums::{
-    Authenticity, EtpFlag, FinancialStatus, IpoFlag, IssueClassification, IssueSubType,
-    LuldRefPriceTier, MarketCategory, Side, ThresholdIndicator,
+    Authenticity, CrossType, EtpFlag, FinancialStatus, ImbalanceDirection, IpoFlag,
+    IpoReleaseQualifier, IssueClassification, IssueSubType, LuldRefPriceTier, MarketCategory,
+    MarketCode, MarketMakerMode, MarketParticipantState, MwcbLevel, OperationalHaltAction,
+    PriceVariationIndicator, RegShoAction, RetailInterest, Side, SystemEventCode,
+    ThresholdIndicator, TradingState,
+};
+use crate::error::DecodeError;
+use crate::price::{Price4, Price8};
+use crate::timestamp::Timestamp;
+use crate::utils::{
+    as_flag, as_u16, as_u32, as_u48, as_u64, put_alpha, put_flag, put_u16, put_u32, put_u48,
+    put_u64, PACKET_HEADER_LENGTH,
 };
-use crate::utils::{as_u16, as_u32, as_u48, as_u64};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Alpha<const N: usize>(pub [u8; N]);
+
+pub type Stock = Alpha<8>;
+pub type Mpid = Alpha<4>;
+
+impl<const N: usize> Alpha<N> {
+    pub fn new(bytes: &[u8]) -> Self {
+        let mut alpha = [0; N];
+        alpha.copy_from_slice(&bytes[..N]);
+        Alpha(alpha)
+    }
+}
+
+impl<const N: usize> Default for Alpha<N> {
+    fn default() -> Self {
+        Alpha([b' '; N])
+    }
+}
+
+impl<const N: usize> From<&str> for Alpha<N> {
+    fn from(value: &str) -> Self {
+        let mut alpha = [b' '; N];
+        for (byte, value) in alpha.iter_mut().zip(value.bytes()) {
+            *byte = value;
+        }
+        Alpha(alpha)
+    }
+}
+
+impl<const N: usize> fmt::Display for Alpha<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match str::from_utf8(&self.0) {
+            Ok(value) => f.pad(value),
+            Err(_) => f.pad(&String::from_utf8_lossy(&self.0)),
+        }
+    }
+}
+
+impl<const N: usize> fmt::Debug for Alpha<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\"", self)
+    }
+}
+
+// Serialized without the space padding.
+impl<const N: usize> Serialize for Alpha<N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.to_string().trim_end())
+    }
+}
+
+fn check_length(bytes: &[u8], expected: usize) -> Result<(), DecodeError> {
+    if bytes.len() < expected {
+        return Err(DecodeError::Truncated {
+            expected,
+            actual: bytes.len(),
+        });
+    }
+    Ok(())
+}
+
+// Body length of each decoded message type, excluding the type byte. Unknown
+// types still carry the stock locate, tracking number and timestamp header.
+fn body_length(msg_type: u8) -> usize {
+    match msg_type {
+        b'A' => 35,
+        b'B' => 18,
+        b'C' => 35,
+        b'D' => 18,
+        b'E' => 30,
+        b'F' => 39,
+        b'H' => 24,
+        b'I' => 49,
+        b'J' => 34,
+        b'K' => 27,
+        b'L' => 25,
+        b'N' => 19,
+        b'O' => 47,
+        b'P' => 43,
+        b'Q' => 39,
+        b'R' => 38,
+        b'S' => 11,
+        b'U' => 34,
+        b'V' => 34,
+        b'W' => 11,
+        b'X' => 22,
+        b'Y' => 19,
+        b'h' => 20,
+        _ => 10,
+    }
+}
 
 #[derive(Debug)]
 pub struct PacketHeader<'a> {
@@ -12,297 +119,1172 @@ pub struct PacketHeader<'a> {
 }
 
 impl<'a> PacketHeader<'a> {
-    pub fn new(bytes: &'a [u8]) -> Self {
-        PacketHeader {
+    pub fn new(bytes: &'a [u8]) -> Result<Self, DecodeError> {
+        check_length(bytes, PACKET_HEADER_LENGTH)?;
+        Ok(PacketHeader {
             _session: &bytes[..10],
             sequence_number: as_u64(&bytes[10..18]),
             message_count: as_u16(&bytes[18..20]),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct PacketBuilder {
+    pub session: [u8; 10],
+    pub sequence_number: u64,
+    messages: Vec<Vec<u8>>,
+}
+
+impl PacketBuilder {
+    pub fn new(session: &str, sequence_number: u64) -> Self {
+        let mut bytes = Vec::with_capacity(10);
+        put_alpha(&mut bytes, session, 10);
+        PacketBuilder {
+            session: bytes.try_into().unwrap(),
+            sequence_number,
+            messages: Vec::new(),
         }
     }
+
+    pub fn add(&mut self, message: &Message) -> &mut Self {
+        self.messages.push(message.encode());
+        self
+    }
+
+    pub fn message_count(&self) -> usize {
+        self.messages.len()
+    }
+
+    // Size of the packet built from the messages added so far.
+    pub fn len(&self) -> usize {
+        PACKET_HEADER_LENGTH + self.messages.iter().map(Vec::len).sum::<usize>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    pub fn clear(&mut self, sequence_number: u64) {
+        self.sequence_number = sequence_number;
+        self.messages.clear();
+    }
+
+    pub fn build(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.len());
+        bytes.extend_from_slice(&self.session);
+        put_u64(&mut bytes, self.sequence_number);
+        put_u16(
+            &mut bytes,
+            u16::try_from(self.message_count()).expect("too many messages for a MoldUDP64 packet"),
+        );
+        for message in self.messages.iter() {
+            bytes.extend_from_slice(message);
+        }
+        bytes
+    }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ExecutedOrder {
     pub stock_locate: u16,
-    tracking_number: u16,
-    timestamp: u64,
+    pub tracking_number: u16,
+    pub timestamp: Timestamp,
     pub reference: u64,
     pub executed_shares: u32,
-    match_number: u64,
+    pub match_number: u64,
 }
 
 impl ExecutedOrder {
-    fn new(bytes: &[u8]) -> Self {
-        ExecutedOrder {
+    fn new(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(ExecutedOrder {
             stock_locate: as_u16(&bytes[..2]),
             tracking_number: as_u16(&bytes[2..4]),
-            timestamp: as_u48(&bytes[4..10]),
+            timestamp: Timestamp(as_u48(&bytes[4..10])),
             reference: as_u64(&bytes[10..18]),
             executed_shares: as_u32(&bytes[18..22]),
             match_number: as_u64(&bytes[22..30]),
-        }
+        })
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        put_u16(bytes, self.stock_locate);
+        put_u16(bytes, self.tracking_number);
+        put_u48(bytes, self.timestamp.0);
+        put_u64(bytes, self.reference);
+        put_u32(bytes, self.executed_shares);
+        put_u64(bytes, self.match_number);
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ExecutedWithPriceOrder {
     pub stock_locate: u16,
-    tracking_number: u16,
-    timestamp: u64,
+    pub tracking_number: u16,
+    pub timestamp: Timestamp,
     pub reference: u64,
     pub executed_shares: u32,
-    match_number: u64,
-    printable: bool,
-    pub price: u32,
+    pub match_number: u64,
+    pub printable: bool,
+    pub price: Price4,
 }
 
 impl ExecutedWithPriceOrder {
-    fn new(bytes: &[u8]) -> Self {
-        ExecutedWithPriceOrder {
+    fn new(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(ExecutedWithPriceOrder {
             stock_locate: as_u16(&bytes[..2]),
             tracking_number: as_u16(&bytes[2..4]),
-            timestamp: as_u48(&bytes[4..10]),
+            timestamp: Timestamp(as_u48(&bytes[4..10])),
             reference: as_u64(&bytes[10..18]),
             executed_shares: as_u32(&bytes[18..22]),
             match_number: as_u64(&bytes[22..30]),
-            printable: match bytes[30] {
-                b'Y' => true,
-                b'N' => false,
-                _ => unreachable!(),
-            },
-            price: as_u32(&bytes[31..39]),
-        }
+            printable: as_flag(bytes[30])?,
+            price: Price4(as_u32(&bytes[31..35])),
+        })
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        put_u16(bytes, self.stock_locate);
+        put_u16(bytes, self.tracking_number);
+        put_u48(bytes, self.timestamp.0);
+        put_u64(bytes, self.reference);
+        put_u32(bytes, self.executed_shares);
+        put_u64(bytes, self.match_number);
+        put_flag(bytes, self.printable);
+        put_u32(bytes, self.price.0);
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct CancelOrder {
     pub stock_locate: u16,
-    tracking_number: u16,
-    timestamp: u64,
+    pub tracking_number: u16,
+    pub timestamp: Timestamp,
     pub reference: u64,
     pub canceled_shares: u32,
 }
 
 impl CancelOrder {
-    fn new(bytes: &[u8]) -> Self {
-        CancelOrder {
+    fn new(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(CancelOrder {
             stock_locate: as_u16(&bytes[..2]),
             tracking_number: as_u16(&bytes[2..4]),
-            timestamp: as_u48(&bytes[4..10]),
+            timestamp: Timestamp(as_u48(&bytes[4..10])),
             reference: as_u64(&bytes[10..18]),
             canceled_shares: as_u32(&bytes[18..22]),
-        }
+        })
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        put_u16(bytes, self.stock_locate);
+        put_u16(bytes, self.tracking_number);
+        put_u48(bytes, self.timestamp.0);
+        put_u64(bytes, self.reference);
+        put_u32(bytes, self.canceled_shares);
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct DeleteOrder {
     pub stock_locate: u16,
-    tracking_number: u16,
-    timestamp: u64,
+    pub tracking_number: u16,
+    pub timestamp: Timestamp,
     pub reference: u64,
 }
 
 impl DeleteOrder {
-    fn new(bytes: &[u8]) -> Self {
-        DeleteOrder {
+    fn new(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(DeleteOrder {
             stock_locate: as_u16(&bytes[..2]),
             tracking_number: as_u16(&bytes[2..4]),
-            timestamp: as_u48(&bytes[4..10]),
+            timestamp: Timestamp(as_u48(&bytes[4..10])),
             reference: as_u64(&bytes[10..18]),
-        }
+        })
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        put_u16(bytes, self.stock_locate);
+        put_u16(bytes, self.tracking_number);
+        put_u48(bytes, self.timestamp.0);
+        put_u64(bytes, self.reference);
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ReplaceOrder {
     pub stock_locate: u16,
-    tracking_number: u16,
-    timestamp: u64,
+    pub tracking_number: u16,
+    pub timestamp: Timestamp,
     pub original_reference: u64,
     pub new_reference: u64,
-    pub price: u32,
     pub shares: u32,
+    pub price: Price4,
 }
 
 impl ReplaceOrder {
-    fn new(bytes: &[u8]) -> Self {
-        ReplaceOrder {
+    fn new(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(ReplaceOrder {
             stock_locate: as_u16(&bytes[..2]),
             tracking_number: as_u16(&bytes[2..4]),
-            timestamp: as_u48(&bytes[4..10]),
+            timestamp: Timestamp(as_u48(&bytes[4..10])),
             original_reference: as_u64(&bytes[10..18]),
             new_reference: as_u64(&bytes[18..26]),
-            price: as_u32(&bytes[26..30]),
-            shares: as_u32(&bytes[30..34]),
-        }
+            shares: as_u32(&bytes[26..30]),
+            price: Price4(as_u32(&bytes[30..34])),
+        })
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        put_u16(bytes, self.stock_locate);
+        put_u16(bytes, self.tracking_number);
+        put_u48(bytes, self.timestamp.0);
+        put_u64(bytes, self.original_reference);
+        put_u64(bytes, self.new_reference);
+        put_u32(bytes, self.shares);
+        put_u32(bytes, self.price.0);
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct AddOrder {
     pub stock_locate: u16,
-    tracking_number: u16,
-    timestamp: u64,
+    pub tracking_number: u16,
+    pub timestamp: Timestamp,
     pub reference: u64,
     pub side: Side,
     pub shares: u32,
-    pub stock: String,
-    pub price: u32,
-    attribution: Option<String>,
+    pub stock: Stock,
+    pub price: Price4,
+    pub attribution: Option<Mpid>,
 }
 
 impl AddOrder {
-    fn new(bytes: &[u8]) -> Self {
-        AddOrder {
+    fn new(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(AddOrder {
             stock_locate: as_u16(&bytes[..2]),
             tracking_number: as_u16(&bytes[2..4]),
-            timestamp: as_u48(&bytes[4..10]),
+            timestamp: Timestamp(as_u48(&bytes[4..10])),
             reference: as_u64(&bytes[10..18]),
-            side: match bytes[18] as char {
-                'B' => Side::Buy,
-                'S' => Side::Sell,
-                _ => unreachable!(),
-            },
+            side: Side::new(bytes[18])?,
             shares: as_u32(&bytes[19..23]),
-            stock: String::from_utf8_lossy(&bytes[23..31]).to_string(),
-            price: as_u32(&bytes[31..35]),
+            stock: Alpha::new(&bytes[23..31]),
+            price: Price4(as_u32(&bytes[31..35])),
             attribution: None,
-        }
+        })
     }
 
-    fn new_with_attribution(bytes: &[u8]) -> Self {
-        AddOrder {
+    fn new_with_attribution(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(AddOrder {
             stock_locate: as_u16(&bytes[..2]),
             tracking_number: as_u16(&bytes[2..4]),
-            timestamp: as_u48(&bytes[4..10]),
+            timestamp: Timestamp(as_u48(&bytes[4..10])),
             reference: as_u64(&bytes[10..18]),
-            side: match bytes[18] as char {
-                'B' => Side::Buy,
-                'S' => Side::Sell,
-                _ => unreachable!(),
-            },
+            side: Side::new(bytes[18])?,
             shares: as_u32(&bytes[19..23]),
-            stock: String::from_utf8_lossy(&bytes[23..31]).to_string(),
-            price: as_u32(&bytes[31..35]),
-            attribution: Some(String::from_utf8_lossy(&bytes[35..39]).to_string()),
+            stock: Alpha::new(&bytes[23..31]),
+            price: Price4(as_u32(&bytes[31..35])),
+            attribution: Some(Alpha::new(&bytes[35..39])),
+        })
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        put_u16(bytes, self.stock_locate);
+        put_u16(bytes, self.tracking_number);
+        put_u48(bytes, self.timestamp.0);
+        put_u64(bytes, self.reference);
+        bytes.push(self.side.to_byte());
+        put_u32(bytes, self.shares);
+        bytes.extend_from_slice(&self.stock.0);
+        put_u32(bytes, self.price.0);
+        if let Some(attribution) = &self.attribution {
+            bytes.extend_from_slice(&attribution.0);
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct StockDirectory {
-    stock_locate: u16,
-    tracking_number: u16,
-    timestamp: u64,
-    stock: String,
-    market_category: MarketCategory,
-    financial_status: FinancialStatus,
-    round_lot_size: u32,
-    round_lots_only: bool,
-    issue_classification: IssueClassification,
-    issue_subtype: IssueSubType,
-    authenticity: Authenticity,
-    short_sale_threshold: ThresholdIndicator,
-    ipo_flag: IpoFlag,
-    luld_ref_price_tier: LuldRefPriceTier,
-    etp_flag: EtpFlag,
-    etp_leverage_factor: u32,
-    inverse_indicator: bool,
+    pub stock_locate: u16,
+    pub tracking_number: u16,
+    pub timestamp: Timestamp,
+    pub stock: Stock,
+    pub market_category: MarketCategory,
+    pub financial_status: FinancialStatus,
+    pub round_lot_size: u32,
+    pub round_lots_only: bool,
+    pub issue_classification: IssueClassification,
+    pub issue_subtype: IssueSubType,
+    pub authenticity: Authenticity,
+    pub short_sale_threshold: ThresholdIndicator,
+    pub ipo_flag: IpoFlag,
+    pub luld_ref_price_tier: LuldRefPriceTier,
+    pub etp_flag: EtpFlag,
+    pub etp_leverage_factor: u32,
+    pub inverse_indicator: bool,
 }
 
 impl StockDirectory {
-    fn new(bytes: &[u8]) -> Self {
-        StockDirectory {
+    fn new(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(StockDirectory {
             stock_locate: as_u16(&bytes[..2]),
             tracking_number: as_u16(&bytes[2..4]),
-            timestamp: as_u48(&bytes[4..10]),
-            stock: String::from_utf8_lossy(&bytes[10..16]).to_string(),
-            market_category: MarketCategory::new(bytes[16]),
-            financial_status: FinancialStatus::new(bytes[17]),
-            round_lot_size: as_u32(&bytes[18..22]),
-            round_lots_only: match &bytes[22] {
-                b'Y' => true,
-                b'N' => false,
-                _ => unreachable!(),
-            },
-            issue_classification: IssueClassification::new(bytes[23]),
-            issue_subtype: IssueSubType::new(&bytes[24..26]),
-            authenticity: match bytes[26] {
-                b'P' => Authenticity::Production,
-                b'T' => Authenticity::Test,
-                _ => unreachable!(),
-            },
-            short_sale_threshold: match bytes[27] {
-                b'Y' => ThresholdIndicator::Restricted,
-                b'N' => ThresholdIndicator::NotRestricted,
-                b' ' => ThresholdIndicator::Unavailable,
-                _ => unreachable!(),
-            },
-            ipo_flag: match bytes[28] {
-                b'Y' => IpoFlag::New,
-                b'N' => IpoFlag::NotNew,
-                b' ' => IpoFlag::Unavailable,
-                _ => unreachable!(),
-            },
-            luld_ref_price_tier: match bytes[29] {
-                b'1' => LuldRefPriceTier::Tier1,
-                b'2' => LuldRefPriceTier::Tier2,
-                b' ' => LuldRefPriceTier::Unavailable,
-                _ => unreachable!(),
-            },
-            etp_flag: match bytes[30] {
-                b'Y' => EtpFlag::Is,
-                b'N' => EtpFlag::IsNot,
-                b' ' => EtpFlag::Unavailable,
-                _ => unreachable!(),
-            },
-            etp_leverage_factor: as_u32(&bytes[31..35]),
-            inverse_indicator: match bytes[35] {
-                b'Y' => true,
-                b'N' => false,
-                _ => unreachable!(),
-            },
-        }
+            timestamp: Timestamp(as_u48(&bytes[4..10])),
+            stock: Alpha::new(&bytes[10..18]),
+            market_category: MarketCategory::new(bytes[18])?,
+            financial_status: FinancialStatus::new(bytes[19])?,
+            round_lot_size: as_u32(&bytes[20..24]),
+            round_lots_only: as_flag(bytes[24])?,
+            issue_classification: IssueClassification::new(bytes[25])?,
+            issue_subtype: IssueSubType::new(&bytes[26..28])?,
+            authenticity: Authenticity::new(bytes[28])?,
+            short_sale_threshold: ThresholdIndicator::new(bytes[29])?,
+            ipo_flag: IpoFlag::new(bytes[30])?,
+            luld_ref_price_tier: LuldRefPriceTier::new(bytes[31])?,
+            etp_flag: EtpFlag::new(bytes[32])?,
+            etp_leverage_factor: as_u32(&bytes[33..37]),
+            inverse_indicator: as_flag(bytes[37])?,
+        })
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        put_u16(bytes, self.stock_locate);
+        put_u16(bytes, self.tracking_number);
+        put_u48(bytes, self.timestamp.0);
+        bytes.extend_from_slice(&self.stock.0);
+        bytes.push(self.market_category.to_byte());
+        bytes.push(self.financial_status.to_byte());
+        put_u32(bytes, self.round_lot_size);
+        put_flag(bytes, self.round_lots_only);
+        bytes.push(self.issue_classification.to_byte());
+        bytes.extend_from_slice(self.issue_subtype.to_bytes());
+        bytes.push(self.authenticity.to_byte());
+        bytes.push(self.short_sale_threshold.to_byte());
+        bytes.push(self.ipo_flag.to_byte());
+        bytes.push(self.luld_ref_price_tier.to_byte());
+        bytes.push(self.etp_flag.to_byte());
+        put_u32(bytes, self.etp_leverage_factor);
+        put_flag(bytes, self.inverse_indicator);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StockTradingAction {
+    pub stock_locate: u16,
+    pub tracking_number: u16,
+    pub timestamp: Timestamp,
+    pub stock: Stock,
+    pub trading_state: TradingState,
+    pub reason: Alpha<4>,
+}
+
+impl StockTradingAction {
+    fn new(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(StockTradingAction {
+            stock_locate: as_u16(&bytes[..2]),
+            tracking_number: as_u16(&bytes[2..4]),
+            timestamp: Timestamp(as_u48(&bytes[4..10])),
+            stock: Alpha::new(&bytes[10..18]),
+            trading_state: TradingState::new(bytes[18])?,
+            reason: Alpha::new(&bytes[20..24]),
+        })
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        put_u16(bytes, self.stock_locate);
+        put_u16(bytes, self.tracking_number);
+        put_u48(bytes, self.timestamp.0);
+        bytes.extend_from_slice(&self.stock.0);
+        bytes.push(self.trading_state.to_byte());
+        bytes.push(b' ');
+        bytes.extend_from_slice(&self.reason.0);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegShoRestriction {
+    pub stock_locate: u16,
+    pub tracking_number: u16,
+    pub timestamp: Timestamp,
+    pub stock: Stock,
+    pub reg_sho_action: RegShoAction,
+}
+
+impl RegShoRestriction {
+    fn new(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(RegShoRestriction {
+            stock_locate: as_u16(&bytes[..2]),
+            tracking_number: as_u16(&bytes[2..4]),
+            timestamp: Timestamp(as_u48(&bytes[4..10])),
+            stock: Alpha::new(&bytes[10..18]),
+            reg_sho_action: RegShoAction::new(bytes[18])?,
+        })
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        put_u16(bytes, self.stock_locate);
+        put_u16(bytes, self.tracking_number);
+        put_u48(bytes, self.timestamp.0);
+        bytes.extend_from_slice(&self.stock.0);
+        bytes.push(self.reg_sho_action.to_byte());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarketParticipantPosition {
+    pub stock_locate: u16,
+    pub tracking_number: u16,
+    pub timestamp: Timestamp,
+    pub mpid: Mpid,
+    pub stock: Stock,
+    pub primary_market_maker: bool,
+    pub market_maker_mode: MarketMakerMode,
+    pub market_participant_state: MarketParticipantState,
+}
+
+impl MarketParticipantPosition {
+    fn new(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(MarketParticipantPosition {
+            stock_locate: as_u16(&bytes[..2]),
+            tracking_number: as_u16(&bytes[2..4]),
+            timestamp: Timestamp(as_u48(&bytes[4..10])),
+            mpid: Alpha::new(&bytes[10..14]),
+            stock: Alpha::new(&bytes[14..22]),
+            primary_market_maker: as_flag(bytes[22])?,
+            market_maker_mode: MarketMakerMode::new(bytes[23])?,
+            market_participant_state: MarketParticipantState::new(bytes[24])?,
+        })
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        put_u16(bytes, self.stock_locate);
+        put_u16(bytes, self.tracking_number);
+        put_u48(bytes, self.timestamp.0);
+        bytes.extend_from_slice(&self.mpid.0);
+        bytes.extend_from_slice(&self.stock.0);
+        put_flag(bytes, self.primary_market_maker);
+        bytes.push(self.market_maker_mode.to_byte());
+        bytes.push(self.market_participant_state.to_byte());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MwcbDeclineLevel {
+    pub stock_locate: u16,
+    pub tracking_number: u16,
+    pub timestamp: Timestamp,
+    pub level1: Price8,
+    pub level2: Price8,
+    pub level3: Price8,
+}
+
+impl MwcbDeclineLevel {
+    fn new(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(MwcbDeclineLevel {
+            stock_locate: as_u16(&bytes[..2]),
+            tracking_number: as_u16(&bytes[2..4]),
+            timestamp: Timestamp(as_u48(&bytes[4..10])),
+            level1: Price8(as_u64(&bytes[10..18])),
+            level2: Price8(as_u64(&bytes[18..26])),
+            level3: Price8(as_u64(&bytes[26..34])),
+        })
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        put_u16(bytes, self.stock_locate);
+        put_u16(bytes, self.tracking_number);
+        put_u48(bytes, self.timestamp.0);
+        put_u64(bytes, self.level1.0);
+        put_u64(bytes, self.level2.0);
+        put_u64(bytes, self.level3.0);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SystemEvent {
+    pub stock_locate: u16,
+    pub tracking_number: u16,
+    pub timestamp: Timestamp,
+    pub event_code: SystemEventCode,
+}
+
+impl SystemEvent {
+    fn new(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(SystemEvent {
+            stock_locate: as_u16(&bytes[..2]),
+            tracking_number: as_u16(&bytes[2..4]),
+            timestamp: Timestamp(as_u48(&bytes[4..10])),
+            event_code: SystemEventCode::new(bytes[10])?,
+        })
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        put_u16(bytes, self.stock_locate);
+        put_u16(bytes, self.tracking_number);
+        put_u48(bytes, self.timestamp.0);
+        bytes.push(self.event_code.to_byte());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MwcbStatus {
+    pub stock_locate: u16,
+    pub tracking_number: u16,
+    pub timestamp: Timestamp,
+    pub breached_level: MwcbLevel,
+}
+
+impl MwcbStatus {
+    fn new(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(MwcbStatus {
+            stock_locate: as_u16(&bytes[..2]),
+            tracking_number: as_u16(&bytes[2..4]),
+            timestamp: Timestamp(as_u48(&bytes[4..10])),
+            breached_level: MwcbLevel::new(bytes[10])?,
+        })
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        put_u16(bytes, self.stock_locate);
+        put_u16(bytes, self.tracking_number);
+        put_u48(bytes, self.timestamp.0);
+        bytes.push(self.breached_level.to_byte());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpoQuotingPeriodUpdate {
+    pub stock_locate: u16,
+    pub tracking_number: u16,
+    pub timestamp: Timestamp,
+    pub stock: Stock,
+    // Carried as seconds since midnight.
+    pub release_time: Timestamp,
+    pub release_qualifier: IpoReleaseQualifier,
+    pub ipo_price: Price4,
+}
+
+impl IpoQuotingPeriodUpdate {
+    fn new(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(IpoQuotingPeriodUpdate {
+            stock_locate: as_u16(&bytes[..2]),
+            tracking_number: as_u16(&bytes[2..4]),
+            timestamp: Timestamp(as_u48(&bytes[4..10])),
+            stock: Alpha::new(&bytes[10..18]),
+            release_time: Timestamp::from_seconds(as_u32(&bytes[18..22]) as u64),
+            release_qualifier: IpoReleaseQualifier::new(bytes[22])?,
+            ipo_price: Price4(as_u32(&bytes[23..27])),
+        })
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        put_u16(bytes, self.stock_locate);
+        put_u16(bytes, self.tracking_number);
+        put_u48(bytes, self.timestamp.0);
+        bytes.extend_from_slice(&self.stock.0);
+        put_u32(bytes, self.release_time.seconds() as u32);
+        bytes.push(self.release_qualifier.to_byte());
+        put_u32(bytes, self.ipo_price.0);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LuldAuctionCollar {
+    pub stock_locate: u16,
+    pub tracking_number: u16,
+    pub timestamp: Timestamp,
+    pub stock: Stock,
+    pub reference_price: Price4,
+    pub upper_price: Price4,
+    pub lower_price: Price4,
+    pub extension: u32,
+}
+
+impl LuldAuctionCollar {
+    fn new(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(LuldAuctionCollar {
+            stock_locate: as_u16(&bytes[..2]),
+            tracking_number: as_u16(&bytes[2..4]),
+            timestamp: Timestamp(as_u48(&bytes[4..10])),
+            stock: Alpha::new(&bytes[10..18]),
+            reference_price: Price4(as_u32(&bytes[18..22])),
+            upper_price: Price4(as_u32(&bytes[22..26])),
+            lower_price: Price4(as_u32(&bytes[26..30])),
+            extension: as_u32(&bytes[30..34]),
+        })
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        put_u16(bytes, self.stock_locate);
+        put_u16(bytes, self.tracking_number);
+        put_u48(bytes, self.timestamp.0);
+        bytes.extend_from_slice(&self.stock.0);
+        put_u32(bytes, self.reference_price.0);
+        put_u32(bytes, self.upper_price.0);
+        put_u32(bytes, self.lower_price.0);
+        put_u32(bytes, self.extension);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OperationalHalt {
+    pub stock_locate: u16,
+    pub tracking_number: u16,
+    pub timestamp: Timestamp,
+    pub stock: Stock,
+    pub market_code: MarketCode,
+    pub action: OperationalHaltAction,
+}
+
+impl OperationalHalt {
+    fn new(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(OperationalHalt {
+            stock_locate: as_u16(&bytes[..2]),
+            tracking_number: as_u16(&bytes[2..4]),
+            timestamp: Timestamp(as_u48(&bytes[4..10])),
+            stock: Alpha::new(&bytes[10..18]),
+            market_code: MarketCode::new(bytes[18])?,
+            action: OperationalHaltAction::new(bytes[19])?,
+        })
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        put_u16(bytes, self.stock_locate);
+        put_u16(bytes, self.tracking_number);
+        put_u48(bytes, self.timestamp.0);
+        bytes.extend_from_slice(&self.stock.0);
+        bytes.push(self.market_code.to_byte());
+        bytes.push(self.action.to_byte());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirectListingPriceDiscovery {
+    pub stock_locate: u16,
+    pub tracking_number: u16,
+    pub timestamp: Timestamp,
+    pub stock: Stock,
+    pub open_eligible: bool,
+    pub min_allowable_price: Price4,
+    pub max_allowable_price: Price4,
+    pub near_execution_price: Price4,
+    pub near_execution_time: Timestamp,
+    pub lower_price_range_collar: Price4,
+    pub upper_price_range_collar: Price4,
+}
+
+impl DirectListingPriceDiscovery {
+    fn new(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(DirectListingPriceDiscovery {
+            stock_locate: as_u16(&bytes[..2]),
+            tracking_number: as_u16(&bytes[2..4]),
+            timestamp: Timestamp(as_u48(&bytes[4..10])),
+            stock: Alpha::new(&bytes[10..18]),
+            open_eligible: as_flag(bytes[18])?,
+            min_allowable_price: Price4(as_u32(&bytes[19..23])),
+            max_allowable_price: Price4(as_u32(&bytes[23..27])),
+            near_execution_price: Price4(as_u32(&bytes[27..31])),
+            near_execution_time: Timestamp(as_u64(&bytes[31..39])),
+            lower_price_range_collar: Price4(as_u32(&bytes[39..43])),
+            upper_price_range_collar: Price4(as_u32(&bytes[43..47])),
+        })
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        put_u16(bytes, self.stock_locate);
+        put_u16(bytes, self.tracking_number);
+        put_u48(bytes, self.timestamp.0);
+        bytes.extend_from_slice(&self.stock.0);
+        put_flag(bytes, self.open_eligible);
+        put_u32(bytes, self.min_allowable_price.0);
+        put_u32(bytes, self.max_allowable_price.0);
+        put_u32(bytes, self.near_execution_price.0);
+        put_u64(bytes, self.near_execution_time.0);
+        put_u32(bytes, self.lower_price_range_collar.0);
+        put_u32(bytes, self.upper_price_range_collar.0);
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NonCrossTrade {
+    pub stock_locate: u16,
+    pub tracking_number: u16,
+    pub timestamp: Timestamp,
+    pub reference: u64,
+    pub side: Side,
+    pub shares: u32,
+    pub stock: Stock,
+    pub price: Price4,
+    pub match_number: u64,
+}
+
+impl NonCrossTrade {
+    fn new(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(NonCrossTrade {
+            stock_locate: as_u16(&bytes[..2]),
+            tracking_number: as_u16(&bytes[2..4]),
+            timestamp: Timestamp(as_u48(&bytes[4..10])),
+            reference: as_u64(&bytes[10..18]),
+            side: Side::new(bytes[18])?,
+            shares: as_u32(&bytes[19..23]),
+            stock: Alpha::new(&bytes[23..31]),
+            price: Price4(as_u32(&bytes[31..35])),
+            match_number: as_u64(&bytes[35..43]),
+        })
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        put_u16(bytes, self.stock_locate);
+        put_u16(bytes, self.tracking_number);
+        put_u48(bytes, self.timestamp.0);
+        put_u64(bytes, self.reference);
+        bytes.push(self.side.to_byte());
+        put_u32(bytes, self.shares);
+        bytes.extend_from_slice(&self.stock.0);
+        put_u32(bytes, self.price.0);
+        put_u64(bytes, self.match_number);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CrossTrade {
+    pub stock_locate: u16,
+    pub tracking_number: u16,
+    pub timestamp: Timestamp,
+    pub shares: u64,
+    pub stock: Stock,
+    pub cross_price: Price4,
+    pub match_number: u64,
+    pub cross_type: CrossType,
+}
+
+impl CrossTrade {
+    fn new(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(CrossTrade {
+            stock_locate: as_u16(&bytes[..2]),
+            tracking_number: as_u16(&bytes[2..4]),
+            timestamp: Timestamp(as_u48(&bytes[4..10])),
+            shares: as_u64(&bytes[10..18]),
+            stock: Alpha::new(&bytes[18..26]),
+            cross_price: Price4(as_u32(&bytes[26..30])),
+            match_number: as_u64(&bytes[30..38]),
+            cross_type: CrossType::new(bytes[38])?,
+        })
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        put_u16(bytes, self.stock_locate);
+        put_u16(bytes, self.tracking_number);
+        put_u48(bytes, self.timestamp.0);
+        put_u64(bytes, self.shares);
+        bytes.extend_from_slice(&self.stock.0);
+        put_u32(bytes, self.cross_price.0);
+        put_u64(bytes, self.match_number);
+        bytes.push(self.cross_type.to_byte());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BrokenTrade {
+    pub stock_locate: u16,
+    pub tracking_number: u16,
+    pub timestamp: Timestamp,
+    pub match_number: u64,
+}
+
+impl BrokenTrade {
+    fn new(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(BrokenTrade {
+            stock_locate: as_u16(&bytes[..2]),
+            tracking_number: as_u16(&bytes[2..4]),
+            timestamp: Timestamp(as_u48(&bytes[4..10])),
+            match_number: as_u64(&bytes[10..18]),
+        })
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        put_u16(bytes, self.stock_locate);
+        put_u16(bytes, self.tracking_number);
+        put_u48(bytes, self.timestamp.0);
+        put_u64(bytes, self.match_number);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NetOrderImbalance {
+    pub stock_locate: u16,
+    pub tracking_number: u16,
+    pub timestamp: Timestamp,
+    pub paired_shares: u64,
+    pub imbalance_shares: u64,
+    pub imbalance_direction: ImbalanceDirection,
+    pub stock: Stock,
+    pub far_price: Price4,
+    pub near_price: Price4,
+    pub current_reference_price: Price4,
+    pub cross_type: CrossType,
+    pub price_variation_indicator: PriceVariationIndicator,
+}
+
+impl NetOrderImbalance {
+    fn new(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(NetOrderImbalance {
+            stock_locate: as_u16(&bytes[..2]),
+            tracking_number: as_u16(&bytes[2..4]),
+            timestamp: Timestamp(as_u48(&bytes[4..10])),
+            paired_shares: as_u64(&bytes[10..18]),
+            imbalance_shares: as_u64(&bytes[18..26]),
+            imbalance_direction: ImbalanceDirection::new(bytes[26])?,
+            stock: Alpha::new(&bytes[27..35]),
+            far_price: Price4(as_u32(&bytes[35..39])),
+            near_price: Price4(as_u32(&bytes[39..43])),
+            current_reference_price: Price4(as_u32(&bytes[43..47])),
+            cross_type: CrossType::new(bytes[47])?,
+            price_variation_indicator: PriceVariationIndicator::new(bytes[48])?,
+        })
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        put_u16(bytes, self.stock_locate);
+        put_u16(bytes, self.tracking_number);
+        put_u48(bytes, self.timestamp.0);
+        put_u64(bytes, self.paired_shares);
+        put_u64(bytes, self.imbalance_shares);
+        bytes.push(self.imbalance_direction.to_byte());
+        bytes.extend_from_slice(&self.stock.0);
+        put_u32(bytes, self.far_price.0);
+        put_u32(bytes, self.near_price.0);
+        put_u32(bytes, self.current_reference_price.0);
+        bytes.push(self.cross_type.to_byte());
+        bytes.push(self.price_variation_indicator.to_byte());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetailPriceImprovement {
+    pub stock_locate: u16,
+    pub tracking_number: u16,
+    pub timestamp: Timestamp,
+    pub stock: Stock,
+    pub interest: RetailInterest,
+}
+
+impl RetailPriceImprovement {
+    fn new(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(RetailPriceImprovement {
+            stock_locate: as_u16(&bytes[..2]),
+            tracking_number: as_u16(&bytes[2..4]),
+            timestamp: Timestamp(as_u48(&bytes[4..10])),
+            stock: Alpha::new(&bytes[10..18]),
+            interest: RetailInterest::new(bytes[18])?,
+        })
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        put_u16(bytes, self.stock_locate);
+        put_u16(bytes, self.tracking_number);
+        put_u48(bytes, self.timestamp.0);
+        bytes.extend_from_slice(&self.stock.0);
+        bytes.push(self.interest.to_byte());
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Body {
     AddOrder(AddOrder),
     ExecutedWithPriceOrder(ExecutedWithPriceOrder),
     DeleteOrder(DeleteOrder),
     ExecutedOrder(ExecutedOrder),
     StockDirectory(StockDirectory),
+    StockTradingAction(StockTradingAction),
     ReplaceOrder(ReplaceOrder),
     CancelOrder(CancelOrder),
-    None,
+    RegShoRestriction(RegShoRestriction),
+    MarketParticipantPosition(MarketParticipantPosition),
+    MwcbDeclineLevel(MwcbDeclineLevel),
+    MwcbStatus(MwcbStatus),
+    IpoQuotingPeriodUpdate(IpoQuotingPeriodUpdate),
+    LuldAuctionCollar(LuldAuctionCollar),
+    OperationalHalt(OperationalHalt),
+    DirectListingPriceDiscovery(DirectListingPriceDiscovery),
+    NonCrossTrade(NonCrossTrade),
+    CrossTrade(CrossTrade),
+    BrokenTrade(BrokenTrade),
+    NetOrderImbalance(NetOrderImbalance),
+    RetailPriceImprovement(RetailPriceImprovement),
+    SystemEvent(SystemEvent),
+    // Types the decoder does not model keep their bytes after the type, so
+    // that they encode back unchanged.
+    Unknown { msg_type: u8, bytes: Vec<u8> },
 }
 
-#[derive(Debug)]
+impl Body {
+    pub fn msg_type(&self) -> u8 {
+        match self {
+            Body::AddOrder(add) if add.attribution.is_some() => b'F',
+            Body::AddOrder(_) => b'A',
+            Body::BrokenTrade(_) => b'B',
+            Body::ExecutedWithPriceOrder(_) => b'C',
+            Body::DeleteOrder(_) => b'D',
+            Body::ExecutedOrder(_) => b'E',
+            Body::NetOrderImbalance(_) => b'I',
+            Body::LuldAuctionCollar(_) => b'J',
+            Body::IpoQuotingPeriodUpdate(_) => b'K',
+            Body::MarketParticipantPosition(_) => b'L',
+            Body::RetailPriceImprovement(_) => b'N',
+            Body::DirectListingPriceDiscovery(_) => b'O',
+            Body::NonCrossTrade(_) => b'P',
+            Body::CrossTrade(_) => b'Q',
+            Body::StockDirectory(_) => b'R',
+            Body::ReplaceOrder(_) => b'U',
+            Body::MwcbDeclineLevel(_) => b'V',
+            Body::MwcbStatus(_) => b'W',
+            Body::CancelOrder(_) => b'X',
+            Body::StockTradingAction(_) => b'H',
+            Body::RegShoRestriction(_) => b'Y',
+            Body::OperationalHalt(_) => b'h',
+            Body::SystemEvent(_) => b'S',
+            Body::Unknown { msg_type, .. } => *msg_type,
+        }
+    }
+
+    pub fn timestamp(&self) -> Timestamp {
+        match self {
+            Body::AddOrder(msg) => msg.timestamp,
+            Body::BrokenTrade(msg) => msg.timestamp,
+            Body::ExecutedWithPriceOrder(msg) => msg.timestamp,
+            Body::DeleteOrder(msg) => msg.timestamp,
+            Body::ExecutedOrder(msg) => msg.timestamp,
+            Body::NetOrderImbalance(msg) => msg.timestamp,
+            Body::LuldAuctionCollar(msg) => msg.timestamp,
+            Body::IpoQuotingPeriodUpdate(msg) => msg.timestamp,
+            Body::MarketParticipantPosition(msg) => msg.timestamp,
+            Body::RetailPriceImprovement(msg) => msg.timestamp,
+            Body::DirectListingPriceDiscovery(msg) => msg.timestamp,
+            Body::NonCrossTrade(msg) => msg.timestamp,
+            Body::CrossTrade(msg) => msg.timestamp,
+            Body::StockDirectory(msg) => msg.timestamp,
+            Body::ReplaceOrder(msg) => msg.timestamp,
+            Body::MwcbDeclineLevel(msg) => msg.timestamp,
+            Body::MwcbStatus(msg) => msg.timestamp,
+            Body::CancelOrder(msg) => msg.timestamp,
+            Body::StockTradingAction(msg) => msg.timestamp,
+            Body::RegShoRestriction(msg) => msg.timestamp,
+            Body::OperationalHalt(msg) => msg.timestamp,
+            Body::SystemEvent(msg) => msg.timestamp,
+            Body::Unknown { bytes, .. } => {
+                bytes.get(4..10).map_or(Timestamp::default(), |timestamp| {
+                    Timestamp(as_u48(timestamp))
+                })
+            }
+        }
+    }
+
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        match self {
+            Body::AddOrder(msg) => msg.encode(bytes),
+            Body::BrokenTrade(msg) => msg.encode(bytes),
+            Body::ExecutedWithPriceOrder(msg) => msg.encode(bytes),
+            Body::DeleteOrder(msg) => msg.encode(bytes),
+            Body::ExecutedOrder(msg) => msg.encode(bytes),
+            Body::NetOrderImbalance(msg) => msg.encode(bytes),
+            Body::LuldAuctionCollar(msg) => msg.encode(bytes),
+            Body::IpoQuotingPeriodUpdate(msg) => msg.encode(bytes),
+            Body::MarketParticipantPosition(msg) => msg.encode(bytes),
+            Body::RetailPriceImprovement(msg) => msg.encode(bytes),
+            Body::DirectListingPriceDiscovery(msg) => msg.encode(bytes),
+            Body::NonCrossTrade(msg) => msg.encode(bytes),
+            Body::CrossTrade(msg) => msg.encode(bytes),
+            Body::StockDirectory(msg) => msg.encode(bytes),
+            Body::ReplaceOrder(msg) => msg.encode(bytes),
+            Body::MwcbDeclineLevel(msg) => msg.encode(bytes),
+            Body::MwcbStatus(msg) => msg.encode(bytes),
+            Body::CancelOrder(msg) => msg.encode(bytes),
+            Body::StockTradingAction(msg) => msg.encode(bytes),
+            Body::RegShoRestriction(msg) => msg.encode(bytes),
+            Body::OperationalHalt(msg) => msg.encode(bytes),
+            Body::SystemEvent(msg) => msg.encode(bytes),
+            Body::Unknown { bytes: body, .. } => bytes.extend_from_slice(body),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Message {
     pub length: u16,
     pub msg_type: u8,
     pub body: Body,
 }
 
+// Borrowed view over a length prefixed message, header and order fields are
+// read on demand and the body is only decoded when asked for.
+#[derive(Debug, Clone, Copy)]
+pub struct MessageView<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> MessageView<'a> {
+    pub fn new(bytes: &'a [u8]) -> Result<Self, DecodeError> {
+        check_length(bytes, 3)?;
+        let length = as_u16(&bytes[..2]) as usize;
+        check_length(bytes, length + 2)?;
+        let bytes = &bytes[..length + 2];
+        check_length(bytes, 3)?;
+        check_length(bytes, body_length(bytes[2]) + 3)?;
+        Ok(MessageView { bytes })
+    }
+
+    pub fn length(&self) -> u16 {
+        as_u16(&self.bytes[..2])
+    }
+
+    pub fn msg_type(&self) -> u8 {
+        self.bytes[2]
+    }
+
+    pub fn stock_locate(&self) -> u16 {
+        as_u16(&self.bytes[3..5])
+    }
+
+    pub fn tracking_number(&self) -> u16 {
+        as_u16(&self.bytes[5..7])
+    }
+
+    pub fn timestamp(&self) -> Timestamp {
+        Timestamp(as_u48(&self.bytes[7..13]))
+    }
+
+    // Order reference, the original one for a replace. Messages without the
+    // field give None, here and in the accessors below.
+    pub fn reference(&self) -> Option<u64> {
+        match self.msg_type() {
+            b'A' | b'C' | b'D' | b'E' | b'F' | b'P' | b'U' | b'X' => {
+                Some(as_u64(&self.bytes[13..21]))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn new_reference(&self) -> Option<u64> {
+        match self.msg_type() {
+            b'U' => Some(as_u64(&self.bytes[21..29])),
+            _ => None,
+        }
+    }
+
+    pub fn side(&self) -> Result<Option<Side>, DecodeError> {
+        match self.msg_type() {
+            b'A' | b'F' | b'P' => Side::new(self.bytes[21]).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    // Shares added, executed, canceled, replaced or traded. Cross trades carry
+    // 8 byte shares and are left out.
+    pub fn shares(&self) -> Option<u32> {
+        let offset = match self.msg_type() {
+            b'A' | b'F' | b'P' => 22,
+            b'C' | b'E' | b'X' => 21,
+            b'U' => 29,
+            _ => return None,
+        };
+        Some(as_u32(&self.bytes[offset..offset + 4]))
+    }
+
+    pub fn price(&self) -> Option<Price4> {
+        let offset = match self.msg_type() {
+            b'A' | b'C' | b'F' | b'P' => 34,
+            b'Q' => 29,
+            b'U' => 33,
+            _ => return None,
+        };
+        Some(Price4(as_u32(&self.bytes[offset..offset + 4])))
+    }
+
+    pub fn stock(&self) -> Option<Stock> {
+        let offset = match self.msg_type() {
+            b'H' | b'J' | b'K' | b'N' | b'O' | b'R' | b'Y' | b'h' => 13,
+            b'L' => 17,
+            b'Q' => 21,
+            b'A' | b'F' | b'P' => 26,
+            b'I' => 30,
+            _ => return None,
+        };
+        Some(Alpha::new(&self.bytes[offset..offset + 8]))
+    }
+
+    pub fn attribution(&self) -> Option<Mpid> {
+        match self.msg_type() {
+            b'F' => Some(Alpha::new(&self.bytes[38..42])),
+            _ => None,
+        }
+    }
+
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    pub fn decode(&self) -> Result<Message, DecodeError> {
+        let bytes = self.bytes;
+        Ok(Message {
+            length: self.length(),
+            msg_type: self.msg_type(),
+            body: match self.msg_type() {
+                b'A' => Body::AddOrder(AddOrder::new(&bytes[3..])?),
+                b'B' => Body::BrokenTrade(BrokenTrade::new(&bytes[3..])?),
+                b'C' => Body::ExecutedWithPriceOrder(ExecutedWithPriceOrder::new(&bytes[3..])?),
+                b'D' => Body::DeleteOrder(DeleteOrder::new(&bytes[3..])?),
+                b'E' => Body::ExecutedOrder(ExecutedOrder::new(&bytes[3..])?),
+                b'F' => Body::AddOrder(AddOrder::new_with_attribution(&bytes[3..])?),
+                b'H' => Body::StockTradingAction(StockTradingAction::new(&bytes[3..])?),
+                b'I' => Body::NetOrderImbalance(NetOrderImbalance::new(&bytes[3..])?),
+                b'J' => Body::LuldAuctionCollar(LuldAuctionCollar::new(&bytes[3..])?),
+                b'K' => Body::IpoQuotingPeriodUpdate(IpoQuotingPeriodUpdate::new(&bytes[3..])?),
+                b'L' => {
+                    Body::MarketParticipantPosition(MarketParticipantPosition::new(&bytes[3..])?)
+                }
+                b'N' => Body::RetailPriceImprovement(RetailPriceImprovement::new(&bytes[3..])?),
+                b'O' => Body::DirectListingPriceDiscovery(DirectListingPriceDiscovery::new(
+                    &bytes[3..],
+                )?),
+                b'P' => Body::NonCrossTrade(NonCrossTrade::new(&bytes[3..])?),
+                b'Q' => Body::CrossTrade(CrossTrade::new(&bytes[3..])?),
+                b'R' => Body::StockDirectory(StockDirectory::new(&bytes[3..])?),
+                b'S' => Body::SystemEvent(SystemEvent::new(&bytes[3..])?),
+                b'U' => Body::ReplaceOrder(ReplaceOrder::new(&bytes[3..])?),
+                b'V' => Body::MwcbDeclineLevel(MwcbDeclineLevel::new(&bytes[3..])?),
+                b'W' => Body::MwcbStatus(MwcbStatus::new(&bytes[3..])?),
+                b'X' => Body::CancelOrder(CancelOrder::new(&bytes[3..])?),
+                b'Y' => Body::RegShoRestriction(RegShoRestriction::new(&bytes[3..])?),
+                b'h' => Body::OperationalHalt(OperationalHalt::new(&bytes[3..])?),
+                msg_type => Body::Unknown {
+                    msg_type,
+                    bytes: bytes[3..].to_vec(),
+                },
+            },
+        })
+    }
+}
+
 impl Message {
-    pub fn new(bytes: &[u8]) -> Self {
+    pub fn new(bytes: &[u8]) -> Result<Self, DecodeError> {
+        MessageView::new(bytes)?.decode()
+    }
+
+    pub fn with_body(body: Body) -> Self {
+        let mut bytes = vec![body.msg_type()];
+        body.encode(&mut bytes);
         Message {
-            length: as_u16(&bytes[..2]),
-            msg_type: bytes[2],
-            body: match bytes[2] {
-                b'A' => Body::AddOrder(AddOrder::new(&bytes[3..])),
-                b'C' => Body::ExecutedWithPriceOrder(ExecutedWithPriceOrder::new(&bytes[3..])),
-                b'D' => Body::DeleteOrder(DeleteOrder::new(&bytes[3..])),
-                b'E' => Body::ExecutedOrder(ExecutedOrder::new(&bytes[3..])),
-                b'F' => Body::AddOrder(AddOrder::new_with_attribution(&bytes[3..])),
-                b'R' => Body::StockDirectory(StockDirectory::new(&bytes[3..])),
-                b'U' => Body::ReplaceOrder(ReplaceOrder::new(&bytes[3..])),
-                b'X' => Body::CancelOrder(CancelOrder::new(&bytes[3..])),
-                _ => Body::None,
-            },
+            length: bytes.len() as u16,
+            msg_type: bytes[0],
+            body,
         }
     }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.length as usize + 2);
+        put_u16(&mut bytes, self.length);
+        bytes.push(self.body.msg_type());
+        self.body.encode(&mut bytes);
+        bytes
+    }
 }
@@ -0,0 +1,101 @@
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::thread;
+
+use crate::messages::MessageView;
+use crate::pipeline::Pipeline;
+use crate::reader::{Reader, StopCondition};
+use crate::utils::{as_u64, put_u64};
+
+// Messages are handed to the workers in batches to keep channel traffic low,
+// each message preceded by the capture time of its packet.
+const BATCH_SIZE: usize = 64 * 1024;
+const BATCHES_IN_FLIGHT: usize = 16;
+const NO_CAPTURE_TIMESTAMP: u64 = u64::MAX;
+
+pub fn shard(stock_locate: u16, threads: usize) -> usize {
+    stock_locate as usize % threads
+}
+
+fn worker(receiver: Receiver<Vec<u8>>) -> Pipeline {
+    let mut pipeline = Pipeline::new();
+    for batch in receiver {
+        let mut data = batch.as_slice();
+        while !data.is_empty() {
+            let capture_timestamp = as_u64(&data[..8]);
+            pipeline.capture_timestamp =
+                Some(capture_timestamp).filter(|timestamp| *timestamp != NO_CAPTURE_TIMESTAMP);
+            // Batches only hold messages already framed by the reader thread.
+            let view = MessageView::new(&data[8..]).unwrap();
+            data = &data[8 + view.bytes().len()..];
+            if let Err(error) = pipeline.process_view(&view) {
+                eprintln!("[locate {}] {}", view.stock_locate(), error);
+            }
+        }
+    }
+    pipeline
+}
+
+// The calling thread reads and frames the packets, each worker owns the
+// locates of its shard so per-locate order is preserved. Locate 0 messages
+// are market-wide and go to every worker.
+pub fn replay_sharded(reader: &mut dyn Reader, stop: StopCondition, threads: usize) -> Pipeline {
+    let threads = threads.max(1);
+    thread::scope(|scope| {
+        let (senders, handles): (Vec<_>, Vec<_>) = (0..threads)
+            .map(|_| {
+                let (sender, receiver) = sync_channel::<Vec<u8>>(BATCHES_IN_FLIGHT);
+                (sender, scope.spawn(move || worker(receiver)))
+            })
+            .unzip();
+        let mut batches = vec![Vec::with_capacity(BATCH_SIZE); threads];
+        let mut sequence_number = 0;
+
+        while let Some(packet) = reader.next_packet() {
+            if stop.is_reached(&packet) {
+                break;
+            }
+            let capture_timestamp = packet.timestamp.unwrap_or(NO_CAPTURE_TIMESTAMP);
+
+            for (index, bytes) in packet.messages().enumerate() {
+                sequence_number = packet.sequence_number + index as u64;
+                let view = match MessageView::new(bytes) {
+                    Ok(view) => view,
+                    Err(error) => {
+                        eprintln!("[{}] {}", packet.sequence_number, error);
+                        continue;
+                    }
+                };
+                let targets = match view.stock_locate() {
+                    0 => 0..threads,
+                    stock_locate => {
+                        let index = shard(stock_locate, threads);
+                        index..index + 1
+                    }
+                };
+                for index in targets {
+                    put_u64(&mut batches[index], capture_timestamp);
+                    batches[index].extend_from_slice(view.bytes());
+                    if batches[index].len() >= BATCH_SIZE {
+                        let batch =
+                            std::mem::replace(&mut batches[index], Vec::with_capacity(BATCH_SIZE));
+                        senders[index].send(batch).unwrap();
+                    }
+                }
+            }
+        }
+
+        for (sender, batch) in senders.into_iter().zip(batches) {
+            if !batch.is_empty() {
+                sender.send(batch).unwrap();
+            }
+        }
+
+        let mut pipelines = handles.into_iter().map(|handle| handle.join().unwrap());
+        let mut pipeline = pipelines.next().unwrap();
+        for other in pipelines {
+            pipeline.merge(other);
+        }
+        pipeline.sequence_number = sequence_number;
+        pipeline
+    })
+}
@@ -0,0 +1,226 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::auctionmanager::AuctionManager;
+use crate::bookmanager::{BookManager, Order, OrderManager};
+use crate::error::DecodeError;
+use crate::messages::{Body, Message, MessageView, Stock};
+use crate::price::Price4;
+use crate::statemanager::StateManager;
+use crate::timestamp::Timestamp;
+use crate::trademanager::TradeManager;
+
+// Restricts processing to a set of symbols. Their locates are learnt from the
+// stock directory, which Nasdaq sends in full before any order message, or
+// from add orders when the capture starts after the directory.
+#[derive(Debug)]
+struct SymbolFilter {
+    symbols: Vec<Stock>,
+    stock_locates: HashSet<u16>,
+    directory_seen: bool,
+    directory_complete: bool,
+}
+
+impl SymbolFilter {
+    fn new(symbols: &[&str]) -> Self {
+        SymbolFilter {
+            symbols: symbols.iter().map(|symbol| Stock::from(*symbol)).collect(),
+            stock_locates: HashSet::new(),
+            directory_seen: false,
+            directory_complete: false,
+        }
+    }
+
+    fn resolved(&self) -> bool {
+        self.directory_complete || self.stock_locates.len() == self.symbols.len()
+    }
+
+    fn is_wanted(&mut self, view: &MessageView) -> bool {
+        let stock_locate = view.stock_locate();
+        if view.msg_type() == b'R' {
+            self.directory_seen = true;
+            return true;
+        }
+        if stock_locate == 0 {
+            return true;
+        }
+        if self.directory_seen {
+            self.directory_complete = true;
+        }
+        !self.resolved() || self.stock_locates.contains(&stock_locate)
+    }
+
+    fn learn(&mut self, stock_locate: u16, stock: Stock) {
+        if self.symbols.contains(&stock) {
+            self.stock_locates.insert(stock_locate);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Pipeline {
+    pub listing: HashMap<u16, Stock>,
+    pub order_manager: OrderManager,
+    pub book_manager: BookManager,
+    pub state_manager: StateManager,
+    pub trade_manager: TradeManager,
+    pub auction_manager: AuctionManager,
+    // Sequence number of the message being processed and capture time of its
+    // packet, set by the replay loop.
+    pub sequence_number: u64,
+    pub capture_timestamp: Option<u64>,
+    // ITCH timestamp of the latest message given to process_view, skipped
+    // and unknown ones included.
+    pub timestamp: Timestamp,
+    filter: Option<SymbolFilter>,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Pipeline {
+            listing: HashMap::new(),
+            order_manager: OrderManager::new(),
+            book_manager: BookManager::new(),
+            state_manager: StateManager::new(),
+            trade_manager: TradeManager::new(),
+            auction_manager: AuctionManager::new(),
+            sequence_number: 0,
+            capture_timestamp: None,
+            timestamp: Timestamp::default(),
+            filter: None,
+        }
+    }
+
+    pub fn with_symbols(symbols: &[&str]) -> Self {
+        Pipeline {
+            filter: Some(SymbolFilter::new(symbols)),
+            ..Self::new()
+        }
+    }
+
+    // Combines pipelines that processed disjoint sets of locates.
+    pub fn merge(&mut self, other: Pipeline) {
+        self.listing.extend(other.listing);
+        self.order_manager.merge(other.order_manager);
+        self.book_manager.merge(other.book_manager);
+        self.state_manager.merge(other.state_manager);
+        self.trade_manager.merge(other.trade_manager);
+        self.auction_manager.merge(other.auction_manager);
+        self.sequence_number = self.sequence_number.max(other.sequence_number);
+        self.capture_timestamp = self.capture_timestamp.max(other.capture_timestamp);
+        self.timestamp = self.timestamp.max(other.timestamp);
+    }
+
+    pub fn stock_locate(&self, symbol: &str) -> Option<u16> {
+        let stock = Stock::from(symbol);
+        self.listing
+            .iter()
+            .find_map(|(key, val)| if *val == stock { Some(*key) } else { None })
+    }
+
+    pub fn symbol(&self, stock_locate: u16) -> Option<Stock> {
+        self.listing.get(&stock_locate).copied().or_else(|| {
+            self.state_manager
+                .state(stock_locate)
+                .map(|state| state.stock)
+        })
+    }
+
+    // Locate 0 carries market-wide messages and is always selected, as is
+    // every locate when no symbols were requested.
+    pub fn is_selected(&self, stock_locate: u16) -> bool {
+        stock_locate == 0
+            || self
+                .filter
+                .as_ref()
+                .is_none_or(|filter| filter.stock_locates.contains(&stock_locate))
+    }
+
+    pub fn selected_stock_locates(&self) -> Vec<u16> {
+        let mut stock_locates: Vec<u16> = self
+            .listing
+            .keys()
+            .copied()
+            .filter(|stock_locate| self.is_selected(*stock_locate))
+            .collect();
+        stock_locates.sort();
+        stock_locates
+    }
+
+    // Messages for other symbols are skipped on their locate alone, without
+    // being decoded. Adds, cancels, deletes and replaces are applied from the
+    // view, other messages are decoded only when a manager consumes them.
+    // Returns whether the message was selected.
+    pub fn process_view(&mut self, view: &MessageView) -> Result<bool, DecodeError> {
+        self.timestamp = view.timestamp();
+        if let Some(filter) = self.filter.as_mut() {
+            if !filter.is_wanted(view) {
+                return Ok(false);
+            }
+        }
+
+        match view.msg_type() {
+            b'A' | b'D' | b'F' | b'U' | b'X' => {
+                let order = Order::from_view(view, &mut self.listing)?;
+                if let (Some(filter), Some(stock)) = (self.filter.as_mut(), view.stock()) {
+                    filter.learn(view.stock_locate(), stock);
+                }
+                self.order_manager.process(&order, &mut self.book_manager);
+            }
+            b'B' | b'C' | b'E' | b'H' | b'I' | b'J' | b'K' | b'L' | b'N' | b'O' | b'P' | b'Q'
+            | b'R' | b'V' | b'W' | b'Y' | b'h' => {
+                let msg = view.decode()?;
+                if let (Some(filter), Body::StockDirectory(dir)) = (self.filter.as_mut(), &msg.body)
+                {
+                    filter.learn(dir.stock_locate, dir.stock);
+                }
+                self.process(&msg);
+            }
+            _ => (),
+        }
+        Ok(true)
+    }
+
+    // Reference price of the indicative auction: the latest NOII reference,
+    // or the last trade before any imbalance was published.
+    pub fn reference_price(&self, stock_locate: u16) -> Option<Price4> {
+        self.auction_manager
+            .latest_imbalance(stock_locate)
+            .map(|(_, imbalance)| imbalance.current_reference_price)
+            .or_else(|| {
+                self.trade_manager
+                    .trades(stock_locate)
+                    .last()
+                    .map(|trade| trade.price)
+            })
+    }
+
+    pub fn process(&mut self, msg: &Message) {
+        match msg.msg_type {
+            b'B' | b'C' | b'E' | b'P' | b'Q' => {
+                self.trade_manager
+                    .process(msg, &self.order_manager, self.capture_timestamp)
+            }
+            _ => (),
+        }
+        match msg.msg_type {
+            b'I' | b'Q' => self.auction_manager.process(msg),
+            _ => (),
+        }
+        match msg.msg_type {
+            b'A' | b'C' | b'D' | b'E' | b'F' | b'U' | b'X' => {
+                let order = Order::new(msg, &mut self.listing);
+                self.order_manager.process(&order, &mut self.book_manager);
+            }
+            b'H' | b'J' | b'K' | b'L' | b'N' | b'O' | b'R' | b'V' | b'W' | b'Y' | b'h' => {
+                self.state_manager.process(msg)
+            }
+            _ => (),
+        }
+    }
+}
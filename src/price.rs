@@ -0,0 +1,118 @@
+use std::fmt;
+use std::ops::{Add, Mul, Sub};
+use std::str::FromStr;
+
+use serde::{Serialize, Serializer};
+
+use crate::error::ParseError;
+
+// Fixed point prices as carried by ITCH: Price(4) fields are 4 byte integers
+// with 4 implied decimals, Price(8) fields 8 byte integers with 8.
+macro_rules! price {
+    ($name:ident, $repr:ty, $decimals:expr) => {
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(pub $repr);
+
+        impl $name {
+            pub const DECIMALS: usize = $decimals;
+            pub const SCALE: $repr = (10 as $repr).pow($decimals);
+
+            pub fn checked_add(self, other: Self) -> Option<Self> {
+                self.0.checked_add(other.0).map($name)
+            }
+
+            pub fn checked_sub(self, other: Self) -> Option<Self> {
+                self.0.checked_sub(other.0).map($name)
+            }
+
+            pub fn abs_diff(self, other: Self) -> Self {
+                $name(self.0.abs_diff(other.0))
+            }
+
+            pub fn to_f64(self) -> f64 {
+                self.0 as f64 / Self::SCALE as f64
+            }
+        }
+
+        impl Add for $name {
+            type Output = Self;
+
+            fn add(self, other: Self) -> Self {
+                $name(self.0 + other.0)
+            }
+        }
+
+        impl Sub for $name {
+            type Output = Self;
+
+            fn sub(self, other: Self) -> Self {
+                $name(self.0 - other.0)
+            }
+        }
+
+        impl Mul<$repr> for $name {
+            type Output = Self;
+
+            fn mul(self, factor: $repr) -> Self {
+                $name(self.0 * factor)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(
+                    f,
+                    "{}.{:0width$}",
+                    self.0 / Self::SCALE,
+                    self.0 % Self::SCALE,
+                    width = Self::DECIMALS
+                )
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ParseError;
+
+            fn from_str(value: &str) -> Result<Self, ParseError> {
+                let error = || ParseError::new("price", value);
+                let (whole, fraction) = match value.split_once('.') {
+                    Some((_, "")) => return Err(error()),
+                    Some(parts) => parts,
+                    None => (value, ""),
+                };
+                let digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+                if whole.is_empty()
+                    || fraction.len() > Self::DECIMALS
+                    || !digits(whole)
+                    || !digits(fraction)
+                {
+                    return Err(error());
+                }
+                let whole: $repr = whole.parse().map_err(|_| error())?;
+                let fraction: $repr = format!("{:0<width$}", fraction, width = Self::DECIMALS)
+                    .parse()
+                    .map_err(|_| error())?;
+                whole
+                    .checked_mul(Self::SCALE)
+                    .and_then(|whole| whole.checked_add(fraction))
+                    .map($name)
+                    .ok_or_else(error)
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+    };
+}
+
+price!(Price4, u32, 4);
+price!(Price8, u64, 8);
+
+impl From<Price4> for Price8 {
+    fn from(price: Price4) -> Self {
+        Price8(price.0 as u64 * 10_000)
+    }
+}
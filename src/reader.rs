@@ -0,0 +1,458 @@
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+use memmap2::Mmap;
+use pcap::{Capture, Offline, Precision};
+
+use crate::messages::PacketHeader;
+use crate::utils::{
+    as_u16, PACKET_HEADER_LENGTH, PACKET_HEADER_OFFSET, UDP_DEST_PORT, UDP_DEST_PORT_OFFSET,
+};
+
+const PCAP_MAGICS: [[u8; 4]; 5] = [
+    [0xd4, 0xc3, 0xb2, 0xa1],
+    [0xa1, 0xb2, 0xc3, 0xd4],
+    [0x4d, 0x3c, 0xb2, 0xa1],
+    [0xa1, 0xb2, 0x3c, 0x4d],
+    [0x0a, 0x0d, 0x0d, 0x0a],
+];
+const PCAP_HEADER_LENGTH: usize = 24;
+const PCAP_RECORD_HEADER_LENGTH: usize = 16;
+const PCAPNG_SECTION_HEADER: [u8; 4] = [0x0a, 0x0d, 0x0d, 0x0a];
+const PCAPNG_BIG_ENDIAN_MAGIC: [u8; 4] = [0x1a, 0x2b, 0x3c, 0x4d];
+const PCAPNG_INTERFACE_DESCRIPTION: u32 = 1;
+const PCAPNG_SIMPLE_PACKET: u32 = 3;
+const PCAPNG_ENHANCED_PACKET: u32 = 6;
+const PCAPNG_OPTION_TSRESOL: u16 = 9;
+const PCAPNG_OPTION_TSOFFSET: u16 = 14;
+
+#[derive(Debug)]
+pub struct Packet<'a> {
+    pub sequence_number: u64,
+    pub message_count: u16,
+    // Capture time in nanoseconds since the Unix epoch, raw ITCH files have
+    // none.
+    pub timestamp: Option<u64>,
+    pub data: &'a [u8],
+}
+
+impl<'a> Packet<'a> {
+    pub fn messages(&self) -> Messages<'a> {
+        Messages {
+            data: self.data,
+            remaining: self.message_count,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Messages<'a> {
+    data: &'a [u8],
+    remaining: u16,
+}
+
+impl<'a> Iterator for Messages<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        if self.remaining == 0 || self.data.len() < 2 {
+            return None;
+        }
+        let length = as_u16(&self.data[..2]) as usize + 2;
+        if self.data.len() < length {
+            return None;
+        }
+        let (message, rest) = self.data.split_at(length);
+        self.data = rest;
+        self.remaining -= 1;
+        Some(message)
+    }
+}
+
+// Replay stops at the first packet past either limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StopCondition {
+    pub sequence_number: u64,
+    pub capture_timestamp: u64,
+}
+
+impl Default for StopCondition {
+    fn default() -> Self {
+        StopCondition {
+            sequence_number: u64::MAX,
+            capture_timestamp: u64::MAX,
+        }
+    }
+}
+
+impl StopCondition {
+    pub fn is_reached(&self, packet: &Packet) -> bool {
+        packet.sequence_number > self.sequence_number
+            || packet
+                .timestamp
+                .is_some_and(|timestamp| timestamp > self.capture_timestamp)
+    }
+}
+
+pub trait Reader {
+    fn next_packet(&mut self) -> Option<Packet<'_>>;
+}
+
+// Ethernet frames carrying MoldUDP64 on the ITCH port, anything else is None.
+fn mold_udp64(frame: &[u8], timestamp: Option<u64>) -> Option<Packet<'_>> {
+    if frame.len() < PACKET_HEADER_OFFSET + PACKET_HEADER_LENGTH {
+        return None;
+    }
+    let udp_dest_port = as_u16(&frame[UDP_DEST_PORT_OFFSET..UDP_DEST_PORT_OFFSET + 2]);
+    if udp_dest_port != UDP_DEST_PORT {
+        return None;
+    }
+    let packet_header = PacketHeader::new(&frame[PACKET_HEADER_OFFSET..]).ok()?;
+    Some(Packet {
+        sequence_number: packet_header.sequence_number,
+        message_count: packet_header.message_count,
+        timestamp,
+        data: &frame[PACKET_HEADER_OFFSET + PACKET_HEADER_LENGTH..],
+    })
+}
+
+pub struct PcapReader {
+    capture: Capture<Offline>,
+    buffer: Vec<u8>,
+}
+
+impl PcapReader {
+    pub fn new(path: &Path) -> Result<Self, pcap::Error> {
+        Ok(PcapReader {
+            capture: Capture::from_file_with_precision(path, Precision::Nano)?,
+            buffer: Vec::new(),
+        })
+    }
+}
+
+impl Reader for PcapReader {
+    fn next_packet(&mut self) -> Option<Packet<'_>> {
+        let (sequence_number, message_count, timestamp) = loop {
+            let frame = self.capture.next_packet().ok()?;
+            let timestamp =
+                frame.header.ts.tv_sec as u64 * 1_000_000_000 + frame.header.ts.tv_usec as u64;
+            if let Some(packet) = mold_udp64(frame.data, Some(timestamp)) {
+                self.buffer.clear();
+                self.buffer.extend_from_slice(packet.data);
+                break (
+                    packet.sequence_number,
+                    packet.message_count,
+                    packet.timestamp,
+                );
+            }
+        };
+        Some(Packet {
+            sequence_number,
+            message_count,
+            timestamp,
+            data: &self.buffer,
+        })
+    }
+}
+
+// Raw ITCH files as distributed by Nasdaq: length prefixed messages with no
+// MoldUDP64 framing, each message is numbered as its own packet.
+pub struct ItchReader<R: Read> {
+    reader: R,
+    buffer: Vec<u8>,
+    sequence_number: u64,
+}
+
+impl ItchReader<BufReader<File>> {
+    pub fn new(path: &Path) -> io::Result<Self> {
+        Ok(ItchReader::from_reader(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R: Read> ItchReader<R> {
+    pub fn from_reader(reader: R) -> Self {
+        ItchReader {
+            reader,
+            buffer: Vec::new(),
+            sequence_number: 0,
+        }
+    }
+}
+
+impl<R: Read> Reader for ItchReader<R> {
+    fn next_packet(&mut self) -> Option<Packet<'_>> {
+        let mut length = [0u8; 2];
+        self.reader.read_exact(&mut length).ok()?;
+        self.buffer.clear();
+        self.buffer.extend_from_slice(&length);
+        self.buffer.resize(as_u16(&length) as usize + 2, 0);
+        self.reader.read_exact(&mut self.buffer[2..]).ok()?;
+        self.sequence_number += 1;
+        Some(Packet {
+            sequence_number: self.sequence_number,
+            message_count: 1,
+            timestamp: None,
+            data: &self.buffer,
+        })
+    }
+}
+
+fn read_u32(bytes: &[u8], big_endian: bool) -> u32 {
+    let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+    if big_endian {
+        u32::from_be_bytes(bytes)
+    } else {
+        u32::from_le_bytes(bytes)
+    }
+}
+
+fn read_u16(bytes: &[u8], big_endian: bool) -> u16 {
+    let bytes = [bytes[0], bytes[1]];
+    if big_endian {
+        u16::from_be_bytes(bytes)
+    } else {
+        u16::from_le_bytes(bytes)
+    }
+}
+
+fn next_pcap_frame<'a>(
+    bytes: &'a [u8],
+    offset: &mut usize,
+    big_endian: bool,
+    nanosecond: bool,
+) -> Option<(&'a [u8], Option<u64>)> {
+    let header = bytes.get(*offset..*offset + PCAP_RECORD_HEADER_LENGTH)?;
+    let start = *offset + PCAP_RECORD_HEADER_LENGTH;
+    let end = start + read_u32(&header[8..12], big_endian) as usize;
+    let frame = bytes.get(start..end)?;
+    *offset = end;
+
+    let seconds = read_u32(&header[..4], big_endian) as u64;
+    let fraction = read_u32(&header[4..8], big_endian) as u64;
+    let timestamp = if nanosecond {
+        seconds * 1_000_000_000 + fraction
+    } else {
+        seconds * 1_000_000_000 + fraction * 1_000
+    };
+    Some((frame, Some(timestamp)))
+}
+
+// Timestamp resolution and offset of a pcapng interface, defaults to
+// microseconds with no offset.
+#[derive(Debug, Clone, Copy)]
+struct Interface {
+    resolution: u8,
+    offset: i64,
+}
+
+impl Interface {
+    fn new(block: &[u8], big_endian: bool) -> Self {
+        let mut interface = Interface {
+            resolution: 6,
+            offset: 0,
+        };
+        let options = block.get(16..block.len() - 4).unwrap_or_default();
+        let mut position = 0;
+        while let Some(header) = options.get(position..position + 4) {
+            let code = read_u16(&header[..2], big_endian);
+            let length = read_u16(&header[2..4], big_endian) as usize;
+            let Some(value) = options.get(position + 4..position + 4 + length) else {
+                break;
+            };
+            match (code, length) {
+                (0, _) => break,
+                (PCAPNG_OPTION_TSRESOL, 1) => interface.resolution = value[0],
+                (PCAPNG_OPTION_TSOFFSET, 8) => {
+                    let value = [
+                        value[0], value[1], value[2], value[3], value[4], value[5], value[6],
+                        value[7],
+                    ];
+                    interface.offset = if big_endian {
+                        i64::from_be_bytes(value)
+                    } else {
+                        i64::from_le_bytes(value)
+                    };
+                }
+                _ => (),
+            }
+            position += 4 + length.div_ceil(4) * 4;
+        }
+        interface
+    }
+
+    // The most significant bit selects a power of two resolution instead of a
+    // power of ten.
+    fn nanoseconds(&self, units: u64) -> u64 {
+        let nanoseconds = if self.resolution & 0x80 != 0 {
+            ((units as u128 * 1_000_000_000) >> (self.resolution & 0x7f).min(127)) as u64
+        } else if self.resolution <= 9 {
+            units.saturating_mul(10u64.pow(9 - self.resolution as u32))
+        } else {
+            units / 10u64.saturating_pow(self.resolution as u32 - 9)
+        };
+        nanoseconds.saturating_add_signed(self.offset.saturating_mul(1_000_000_000))
+    }
+}
+
+// Walks pcapng blocks up to the next packet block. Each section header sets
+// the byte order of the blocks that follow it and starts a new list of
+// interfaces, which enhanced packet blocks refer to by index.
+fn next_pcapng_frame<'a>(
+    bytes: &'a [u8],
+    offset: &mut usize,
+    big_endian: &mut bool,
+    interfaces: &mut Vec<Interface>,
+) -> Option<(&'a [u8], Option<u64>)> {
+    loop {
+        let header = bytes.get(*offset..*offset + 12)?;
+        if header[..4] == PCAPNG_SECTION_HEADER {
+            *big_endian = header[8..12] == PCAPNG_BIG_ENDIAN_MAGIC;
+            interfaces.clear();
+        }
+        let block_type = read_u32(&header[..4], *big_endian);
+        let length = read_u32(&header[4..8], *big_endian) as usize;
+        if length < 12 {
+            return None;
+        }
+        let block = bytes.get(*offset..*offset + length)?;
+        *offset += length;
+
+        match block_type {
+            PCAPNG_INTERFACE_DESCRIPTION => interfaces.push(Interface::new(block, *big_endian)),
+            PCAPNG_ENHANCED_PACKET => {
+                let header = block.get(8..28)?;
+                let interface = interfaces.get(read_u32(&header[..4], *big_endian) as usize);
+                let units = (read_u32(&header[4..8], *big_endian) as u64) << 32
+                    | read_u32(&header[8..12], *big_endian) as u64;
+                let captured = read_u32(&header[12..16], *big_endian) as usize;
+                let timestamp = interface.map(|interface| interface.nanoseconds(units));
+                return Some((block.get(28..28 + captured)?, timestamp));
+            }
+            PCAPNG_SIMPLE_PACKET => {
+                let captured =
+                    (read_u32(&block[8..12], *big_endian) as usize).min(length.saturating_sub(16));
+                return Some((block.get(12..12 + captured)?, None));
+            }
+            _ => (),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Format {
+    Pcap {
+        big_endian: bool,
+        nanosecond: bool,
+    },
+    Pcapng {
+        big_endian: bool,
+        interfaces: Vec<Interface>,
+    },
+    Itch {
+        sequence_number: u64,
+    },
+}
+
+// Walks the records of a whole capture held in memory, usually a mapped file,
+// so packets borrow the capture instead of being copied.
+pub struct MmapReader<B: AsRef<[u8]> = Mmap> {
+    bytes: B,
+    offset: usize,
+    format: Format,
+}
+
+impl MmapReader {
+    pub fn new(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // The capture must not be truncated while it is mapped.
+        let map = unsafe { Mmap::map(&file)? };
+        Ok(MmapReader::from_bytes(map))
+    }
+}
+
+impl<B: AsRef<[u8]>> MmapReader<B> {
+    pub fn from_bytes(bytes: B) -> Self {
+        let magic = bytes.as_ref().get(..4).unwrap_or_default();
+        let pcap = |big_endian, nanosecond| {
+            (
+                Format::Pcap {
+                    big_endian,
+                    nanosecond,
+                },
+                PCAP_HEADER_LENGTH,
+            )
+        };
+        let (format, offset) = match magic {
+            [0xd4, 0xc3, 0xb2, 0xa1] => pcap(false, false),
+            [0x4d, 0x3c, 0xb2, 0xa1] => pcap(false, true),
+            [0xa1, 0xb2, 0xc3, 0xd4] => pcap(true, false),
+            [0xa1, 0xb2, 0x3c, 0x4d] => pcap(true, true),
+            [0x0a, 0x0d, 0x0d, 0x0a] => (
+                Format::Pcapng {
+                    big_endian: false,
+                    interfaces: Vec::new(),
+                },
+                0,
+            ),
+            _ => (Format::Itch { sequence_number: 0 }, 0),
+        };
+        MmapReader {
+            bytes,
+            offset,
+            format,
+        }
+    }
+}
+
+impl<B: AsRef<[u8]>> Reader for MmapReader<B> {
+    fn next_packet(&mut self) -> Option<Packet<'_>> {
+        let bytes = self.bytes.as_ref();
+        loop {
+            let (frame, timestamp) = match &mut self.format {
+                Format::Pcap {
+                    big_endian,
+                    nanosecond,
+                } => next_pcap_frame(bytes, &mut self.offset, *big_endian, *nanosecond)?,
+                Format::Pcapng {
+                    big_endian,
+                    interfaces,
+                } => next_pcapng_frame(bytes, &mut self.offset, big_endian, interfaces)?,
+                Format::Itch { sequence_number } => {
+                    let length = as_u16(bytes.get(self.offset..self.offset + 2)?) as usize + 2;
+                    let message = bytes.get(self.offset..self.offset + length)?;
+                    self.offset += length;
+                    *sequence_number += 1;
+                    return Some(Packet {
+                        sequence_number: *sequence_number,
+                        message_count: 1,
+                        timestamp: None,
+                        data: message,
+                    });
+                }
+            };
+            if let Some(packet) = mold_udp64(frame, timestamp) {
+                return Some(packet);
+            }
+        }
+    }
+}
+
+pub fn open(path: &Path) -> io::Result<Box<dyn Reader>> {
+    Ok(Box::new(MmapReader::new(path)?))
+}
+
+// Streams the capture through libpcap and buffered reads instead of mapping it.
+pub fn open_libpcap(path: &Path) -> io::Result<Box<dyn Reader>> {
+    let mut magic = [0u8; 4];
+    let is_pcap = match File::open(path)?.read_exact(&mut magic) {
+        Ok(()) => PCAP_MAGICS.contains(&magic),
+        Err(_) => false,
+    };
+
+    if is_pcap {
+        Ok(Box::new(PcapReader::new(path).map_err(io::Error::other)?))
+    } else {
+        Ok(Box::new(ItchReader::new(path)?))
+    }
+}
@@ -0,0 +1,298 @@
+use serde::Serialize;
+
+use crate::auctionmanager::{Auction, Imbalance};
+use crate::bookmanager::{Bbo, IndicativeAuction, LevelDelta, PriceLevel};
+use crate::enums::{CrossType, LevelAction, Side, TradingState};
+use crate::error::DecodeError;
+use crate::messages::{Mpid, Stock, StockDirectory};
+use crate::pipeline::Pipeline;
+use crate::price::Price4;
+use crate::statemanager::{Event, MarketState, ParticipantPosition, SymbolState};
+use crate::timestamp::Timestamp;
+use crate::utils::{as_u16, as_u32, as_u64, put_u16, put_u32, put_u64};
+
+// JSON counterparts of the text reports. Each report is printed as a single
+// line, so that the reports of a replay form an NDJSON stream.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Report<'a> {
+    Market(MarketReport<'a>),
+    Snapshot(Snapshot<'a>),
+}
+
+impl Report<'_> {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct MarketReport<'a> {
+    pub sequence: u64,
+    pub timestamp: Timestamp,
+    #[serde(flatten)]
+    pub market: &'a MarketState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub directory: Option<Vec<&'a StockDirectory>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub events: Option<&'a [Event]>,
+}
+
+impl<'a> MarketReport<'a> {
+    pub fn new(pipeline: &'a Pipeline) -> Self {
+        MarketReport {
+            sequence: pipeline.sequence_number,
+            timestamp: pipeline.timestamp,
+            market: pipeline.state_manager.market(),
+            directory: None,
+            events: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Snapshot<'a> {
+    pub symbol: Stock,
+    pub locate: u16,
+    pub sequence: u64,
+    pub timestamp: Timestamp,
+    pub capture_timestamp: Option<u64>,
+    pub trading_state: Option<TradingState>,
+    pub bids: Vec<PriceLevel>,
+    pub asks: Vec<PriceLevel>,
+    pub state: Option<&'a SymbolState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub participants: Option<Participants<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auctions: Option<&'a [Auction]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indicative: Option<Indicative<'a>>,
+}
+
+impl<'a> Snapshot<'a> {
+    pub fn new(pipeline: &'a Pipeline, stock_locate: u16, book_depth: usize) -> Self {
+        let state = pipeline.state_manager.state(stock_locate);
+        Snapshot {
+            symbol: pipeline.symbol(stock_locate).unwrap_or_default(),
+            locate: stock_locate,
+            sequence: pipeline.sequence_number,
+            timestamp: pipeline.timestamp,
+            capture_timestamp: pipeline.capture_timestamp,
+            trading_state: state.and_then(|state| state.trading_state),
+            bids: pipeline
+                .book_manager
+                .levels(stock_locate, Side::Buy, book_depth),
+            asks: pipeline
+                .book_manager
+                .levels(stock_locate, Side::Sell, book_depth),
+            state,
+            participants: None,
+            auctions: None,
+            indicative: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Participant<'a> {
+    pub mpid: Mpid,
+    #[serde(flatten)]
+    pub position: &'a ParticipantPosition,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Quoting {
+    pub price: Price4,
+    pub mpids: Vec<Mpid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Participants<'a> {
+    pub positions: Vec<Participant<'a>>,
+    pub bids: Vec<Quoting>,
+    pub asks: Vec<Quoting>,
+}
+
+impl<'a> Participants<'a> {
+    pub fn new(pipeline: &'a Pipeline, stock_locate: u16, book_depth: usize) -> Self {
+        let state_manager = &pipeline.state_manager;
+        let quoting = |side| {
+            state_manager
+                .quoting(
+                    stock_locate,
+                    &pipeline.book_manager.levels(stock_locate, side, book_depth),
+                    &pipeline.order_manager.attributions(stock_locate, side),
+                )
+                .into_iter()
+                .map(|(price, mpids)| Quoting { price, mpids })
+                .collect()
+        };
+        Participants {
+            positions: state_manager
+                .participants(stock_locate)
+                .into_iter()
+                .map(|(mpid, position)| Participant {
+                    mpid: *mpid,
+                    position,
+                })
+                .collect(),
+            bids: quoting(Side::Buy),
+            asks: quoting(Side::Sell),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LatestImbalance<'a> {
+    pub cross_type: CrossType,
+    #[serde(flatten)]
+    pub imbalance: &'a Imbalance,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Indicative<'a> {
+    pub reference_price: Option<Price4>,
+    pub auction: Option<IndicativeAuction>,
+    pub imbalance: Option<LatestImbalance<'a>>,
+}
+
+impl<'a> Indicative<'a> {
+    pub fn new(pipeline: &'a Pipeline, stock_locate: u16) -> Self {
+        let reference_price = pipeline.reference_price(stock_locate);
+        Indicative {
+            reference_price,
+            auction: pipeline
+                .book_manager
+                .indicative_auction(stock_locate, reference_price),
+            imbalance: pipeline.auction_manager.latest_imbalance(stock_locate).map(
+                |(cross_type, imbalance)| LatestImbalance {
+                    cross_type,
+                    imbalance,
+                },
+            ),
+        }
+    }
+}
+
+// Record of the BBO stream, printed whenever the best bid or offer of a
+// selected symbol changes price or shares.
+#[derive(Debug, Serialize)]
+pub struct BboChange {
+    pub symbol: Stock,
+    pub locate: u16,
+    pub sequence: u64,
+    pub timestamp: Timestamp,
+    pub capture_timestamp: Option<u64>,
+    #[serde(flatten)]
+    pub bbo: Bbo,
+}
+
+impl BboChange {
+    pub const CSV_HEADER: &'static str = "symbol,locate,sequence,timestamp,capture_timestamp,bid_price,bid_shares,ask_price,ask_shares";
+
+    pub fn new(pipeline: &Pipeline, stock_locate: u16, bbo: Bbo) -> Self {
+        BboChange {
+            symbol: pipeline.symbol(stock_locate).unwrap_or_default(),
+            locate: stock_locate,
+            sequence: pipeline.sequence_number,
+            timestamp: pipeline.timestamp,
+            capture_timestamp: pipeline.capture_timestamp,
+            bbo,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn to_csv(&self) -> String {
+        let optional = |value: Option<String>| value.unwrap_or_default();
+        format!(
+            "{},{},{},{},{},{},{},{},{}",
+            self.symbol.to_string().trim_end(),
+            self.locate,
+            self.sequence,
+            self.timestamp.0,
+            optional(
+                self.capture_timestamp
+                    .map(|timestamp| timestamp.to_string())
+            ),
+            optional(self.bbo.bid_price.map(|price| price.to_string())),
+            self.bbo.bid_shares,
+            optional(self.bbo.ask_price.map(|price| price.to_string())),
+            self.bbo.ask_shares
+        )
+    }
+}
+
+// Record of the L2 delta stream, one per level updated by a message. The
+// binary encoding is a fixed 48 byte big-endian record, without capture time
+// when encoded as 0:
+//   sequence u64, timestamp u64, capture_timestamp u64, locate u16,
+//   symbol 8 bytes, side 'B'/'S', action 'N'/'C'/'D', price u32 Price(4),
+//   shares u32, orders u32
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LevelUpdate {
+    pub symbol: Stock,
+    pub locate: u16,
+    pub sequence: u64,
+    pub timestamp: Timestamp,
+    pub capture_timestamp: Option<u64>,
+    #[serde(flatten)]
+    pub delta: LevelDelta,
+}
+
+impl LevelUpdate {
+    pub const LENGTH: usize = 48;
+
+    pub fn new(pipeline: &Pipeline, stock_locate: u16, delta: LevelDelta) -> Self {
+        LevelUpdate {
+            symbol: pipeline.symbol(stock_locate).unwrap_or_default(),
+            locate: stock_locate,
+            sequence: pipeline.sequence_number,
+            timestamp: pipeline.timestamp,
+            capture_timestamp: pipeline.capture_timestamp,
+            delta,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn encode(&self, bytes: &mut Vec<u8>) {
+        put_u64(bytes, self.sequence);
+        put_u64(bytes, self.timestamp.0);
+        put_u64(bytes, self.capture_timestamp.unwrap_or(0));
+        put_u16(bytes, self.locate);
+        bytes.extend_from_slice(&self.symbol.0);
+        bytes.push(self.delta.side.to_byte());
+        bytes.push(self.delta.action.to_byte());
+        put_u32(bytes, self.delta.price.0);
+        put_u32(bytes, self.delta.shares);
+        put_u32(bytes, self.delta.orders);
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        if bytes.len() < Self::LENGTH {
+            return Err(DecodeError::Truncated {
+                expected: Self::LENGTH,
+                actual: bytes.len(),
+            });
+        }
+        Ok(LevelUpdate {
+            sequence: as_u64(&bytes[..8]),
+            timestamp: Timestamp(as_u64(&bytes[8..16])),
+            capture_timestamp: Some(as_u64(&bytes[16..24])).filter(|timestamp| *timestamp != 0),
+            locate: as_u16(&bytes[24..26]),
+            symbol: Stock::new(&bytes[26..34]),
+            delta: LevelDelta {
+                side: Side::new(bytes[34])?,
+                action: LevelAction::new(bytes[35])?,
+                price: Price4(as_u32(&bytes[36..40])),
+                shares: as_u32(&bytes[40..44]),
+                orders: as_u32(&bytes[44..48]),
+            },
+        })
+    }
+}
@@ -0,0 +1,426 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::bookmanager::PriceLevel;
+use crate::enums::{
+    IpoReleaseQualifier, MarketCode, MarketMakerMode, MarketParticipantState, MwcbLevel,
+    OperationalHaltAction, RegShoAction, RetailInterest, ThresholdIndicator, TradingState,
+};
+use crate::messages::{Alpha, Body, Message, Mpid, Stock, StockDirectory};
+use crate::price::{Price4, Price8};
+use crate::timestamp::Timestamp;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ParticipantPosition {
+    pub primary_market_maker: bool,
+    pub market_maker_mode: MarketMakerMode,
+    pub market_participant_state: MarketParticipantState,
+}
+
+impl ParticipantPosition {
+    // Active market makers in normal mode, the others carry no quoting
+    // obligation on the book.
+    pub fn is_quoting(&self) -> bool {
+        self.market_participant_state == MarketParticipantState::Active
+            && self.market_maker_mode == MarketMakerMode::Normal
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct IpoQuoting {
+    pub release_time: Timestamp,
+    pub release_qualifier: IpoReleaseQualifier,
+    pub ipo_price: Price4,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct AuctionCollar {
+    pub reference_price: Price4,
+    pub upper_price: Price4,
+    pub lower_price: Price4,
+    pub extension: u32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct PriceDiscovery {
+    pub open_eligible: bool,
+    pub min_allowable_price: Price4,
+    pub max_allowable_price: Price4,
+    pub near_execution_price: Price4,
+    pub near_execution_time: Timestamp,
+    pub lower_price_range_collar: Price4,
+    pub upper_price_range_collar: Price4,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct RetailInterestState {
+    pub interest: RetailInterest,
+    pub since: Timestamp,
+}
+
+// The directory and participants have reports of their own.
+#[derive(Debug, Default, Serialize)]
+pub struct SymbolState {
+    #[serde(skip)]
+    pub stock: Stock,
+    #[serde(skip)]
+    pub directory: Option<StockDirectory>,
+    pub trading_state: Option<TradingState>,
+    pub trading_reason: Alpha<4>,
+    pub short_sale_threshold: Option<ThresholdIndicator>,
+    pub reg_sho_action: Option<RegShoAction>,
+    #[serde(skip)]
+    pub participants: HashMap<Mpid, ParticipantPosition>,
+    pub ipo_quoting: Option<IpoQuoting>,
+    pub auction_collar: Option<AuctionCollar>,
+    pub operational_halts: HashMap<MarketCode, OperationalHaltAction>,
+    pub price_discovery: Option<PriceDiscovery>,
+    pub retail_interest: Option<RetailInterestState>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct MarketState {
+    pub mwcb_levels: Option<(Price8, Price8, Price8)>,
+    pub mwcb_breached_level: Option<MwcbLevel>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(tag = "event")]
+pub enum Event {
+    MwcbBreach {
+        timestamp: Timestamp,
+        level: MwcbLevel,
+    },
+}
+
+#[derive(Debug)]
+pub struct StateManager {
+    states_per_stock_locate: HashMap<u16, SymbolState>,
+    market: MarketState,
+    events: Vec<Event>,
+}
+
+impl Default for StateManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StateManager {
+    pub fn new() -> Self {
+        StateManager {
+            states_per_stock_locate: HashMap::new(),
+            market: MarketState::default(),
+            events: Vec::new(),
+        }
+    }
+
+    // Market-wide state is kept from self: every shard of a replay sees the
+    // same locate 0 messages.
+    pub fn merge(&mut self, other: StateManager) {
+        self.states_per_stock_locate
+            .extend(other.states_per_stock_locate);
+    }
+
+    pub fn state(&self, stock_locate: u16) -> Option<&SymbolState> {
+        self.states_per_stock_locate.get(&stock_locate)
+    }
+
+    pub fn market(&self) -> &MarketState {
+        &self.market
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    pub fn directory(&self) -> Vec<&StockDirectory> {
+        let mut directory: Vec<&StockDirectory> = self
+            .states_per_stock_locate
+            .values()
+            .filter_map(|state| state.directory.as_ref())
+            .collect();
+        directory.sort_by_key(|dir| dir.stock_locate);
+        directory
+    }
+
+    pub fn participants(&self, stock_locate: u16) -> Vec<(&Mpid, &ParticipantPosition)> {
+        let mut participants: Vec<(&Mpid, &ParticipantPosition)> = self
+            .state(stock_locate)
+            .map(|state| state.participants.iter().collect())
+            .unwrap_or_default();
+        participants.sort_by(|a, b| a.0.cmp(b.0));
+        participants
+    }
+
+    // Quoting market makers attributed to each of the given book levels, levels
+    // quoted by none of them are left out.
+    pub fn quoting(
+        &self,
+        stock_locate: u16,
+        levels: &[PriceLevel],
+        attributions: &HashMap<Price4, Vec<Mpid>>,
+    ) -> Vec<(Price4, Vec<Mpid>)> {
+        let Some(state) = self.state(stock_locate) else {
+            return Vec::new();
+        };
+        levels
+            .iter()
+            .filter_map(|level| {
+                let mpids: Vec<Mpid> = attributions
+                    .get(&level.price)?
+                    .iter()
+                    .filter(|mpid| {
+                        state
+                            .participants
+                            .get(*mpid)
+                            .is_some_and(ParticipantPosition::is_quoting)
+                    })
+                    .copied()
+                    .collect();
+                (!mpids.is_empty()).then_some((level.price, mpids))
+            })
+            .collect()
+    }
+
+    pub fn process(&mut self, message: &Message) {
+        match &message.body {
+            Body::StockDirectory(dir) => {
+                let state = self
+                    .states_per_stock_locate
+                    .entry(dir.stock_locate)
+                    .or_default();
+                state.stock = dir.stock;
+                state.directory = Some(*dir);
+                state.short_sale_threshold = Some(dir.short_sale_threshold);
+            }
+            Body::StockTradingAction(action) => {
+                let state = self
+                    .states_per_stock_locate
+                    .entry(action.stock_locate)
+                    .or_default();
+                state.stock = action.stock;
+                state.trading_state = Some(action.trading_state);
+                state.trading_reason = action.reason;
+            }
+            Body::RegShoRestriction(reg_sho) => {
+                let state = self
+                    .states_per_stock_locate
+                    .entry(reg_sho.stock_locate)
+                    .or_default();
+                state.stock = reg_sho.stock;
+                state.reg_sho_action = Some(reg_sho.reg_sho_action);
+            }
+            Body::MarketParticipantPosition(position) => {
+                let state = self
+                    .states_per_stock_locate
+                    .entry(position.stock_locate)
+                    .or_default();
+                state.stock = position.stock;
+                state.participants.insert(
+                    position.mpid,
+                    ParticipantPosition {
+                        primary_market_maker: position.primary_market_maker,
+                        market_maker_mode: position.market_maker_mode,
+                        market_participant_state: position.market_participant_state,
+                    },
+                );
+            }
+            Body::IpoQuotingPeriodUpdate(ipo) => {
+                let state = self
+                    .states_per_stock_locate
+                    .entry(ipo.stock_locate)
+                    .or_default();
+                state.stock = ipo.stock;
+                state.ipo_quoting = Some(IpoQuoting {
+                    release_time: ipo.release_time,
+                    release_qualifier: ipo.release_qualifier,
+                    ipo_price: ipo.ipo_price,
+                });
+            }
+            Body::LuldAuctionCollar(collar) => {
+                let state = self
+                    .states_per_stock_locate
+                    .entry(collar.stock_locate)
+                    .or_default();
+                state.stock = collar.stock;
+                state.auction_collar = Some(AuctionCollar {
+                    reference_price: collar.reference_price,
+                    upper_price: collar.upper_price,
+                    lower_price: collar.lower_price,
+                    extension: collar.extension,
+                });
+            }
+            Body::OperationalHalt(halt) => {
+                let state = self
+                    .states_per_stock_locate
+                    .entry(halt.stock_locate)
+                    .or_default();
+                state.stock = halt.stock;
+                state
+                    .operational_halts
+                    .insert(halt.market_code, halt.action);
+            }
+            Body::DirectListingPriceDiscovery(discovery) => {
+                let state = self
+                    .states_per_stock_locate
+                    .entry(discovery.stock_locate)
+                    .or_default();
+                state.stock = discovery.stock;
+                state.price_discovery = Some(PriceDiscovery {
+                    open_eligible: discovery.open_eligible,
+                    min_allowable_price: discovery.min_allowable_price,
+                    max_allowable_price: discovery.max_allowable_price,
+                    near_execution_price: discovery.near_execution_price,
+                    near_execution_time: discovery.near_execution_time,
+                    lower_price_range_collar: discovery.lower_price_range_collar,
+                    upper_price_range_collar: discovery.upper_price_range_collar,
+                });
+            }
+            Body::RetailPriceImprovement(rpi) => {
+                let state = self
+                    .states_per_stock_locate
+                    .entry(rpi.stock_locate)
+                    .or_default();
+                state.stock = rpi.stock;
+                state.retail_interest = Some(RetailInterestState {
+                    interest: rpi.interest,
+                    since: rpi.timestamp,
+                });
+            }
+            Body::MwcbDeclineLevel(decline) => {
+                self.market.mwcb_levels = Some((decline.level1, decline.level2, decline.level3));
+            }
+            Body::MwcbStatus(status) => {
+                self.market.mwcb_breached_level = Some(status.breached_level);
+                self.events.push(Event::MwcbBreach {
+                    timestamp: status.timestamp,
+                    level: status.breached_level,
+                });
+            }
+            _ => (),
+        }
+    }
+
+    pub fn display_state(&self, stock_locate: u16) {
+        if let Some((level1, level2, level3)) = self.market.mwcb_levels {
+            println!("MWCB levels: {} / {} / {}", level1, level2, level3);
+        }
+        if let Some(level) = self.market.mwcb_breached_level {
+            println!("MWCB breached: {:?}", level);
+        }
+        if let Some(state) = self.state(stock_locate) {
+            if let Some(trading_state) = state.trading_state {
+                match state.trading_reason.to_string().trim_end() {
+                    "" => println!("Trading state: {:?}", trading_state),
+                    reason => println!("Trading state: {:?} ({})", trading_state, reason),
+                }
+            }
+            if let Some(threshold) = state.short_sale_threshold {
+                println!("Short sale threshold: {:?}", threshold);
+            }
+            if let Some(action) = state.reg_sho_action {
+                println!("Reg SHO: {:?}", action);
+            }
+            if let Some(rpi) = state.retail_interest {
+                println!("RPI: {:?} (since {})", rpi.interest, rpi.since);
+            }
+            if let Some(ipo) = state.ipo_quoting {
+                println!(
+                    "IPO release: {} ({:?}) @ {}",
+                    ipo.release_time, ipo.release_qualifier, ipo.ipo_price
+                );
+            }
+            if let Some(collar) = state.auction_collar {
+                println!(
+                    "Auction collar: {} [{} - {}] extension: {}",
+                    collar.reference_price,
+                    collar.lower_price,
+                    collar.upper_price,
+                    collar.extension
+                );
+            }
+            for market_code in [MarketCode::Nasdaq, MarketCode::Bx, MarketCode::Psx] {
+                if let Some(action) = state.operational_halts.get(&market_code) {
+                    println!("Operational halt {:?}: {:?}", market_code, action);
+                }
+            }
+            if let Some(discovery) = state.price_discovery {
+                println!(
+                    "Price discovery: eligible: {} near: {} @ {} allowable: [{} - {}] collar: [{} - {}]",
+                    discovery.open_eligible,
+                    discovery.near_execution_price,
+                    discovery.near_execution_time,
+                    discovery.min_allowable_price,
+                    discovery.max_allowable_price,
+                    discovery.lower_price_range_collar,
+                    discovery.upper_price_range_collar
+                );
+            }
+        }
+    }
+
+    pub fn display_participants(
+        &self,
+        stock_locate: u16,
+        bids: &[(Price4, Vec<Mpid>)],
+        asks: &[(Price4, Vec<Mpid>)],
+    ) {
+        if self.state(stock_locate).is_some() {
+            println!("----- Participants -----");
+            for (mpid, position) in self.participants(stock_locate) {
+                println!(
+                    "{} primary: {} mode: {:?} state: {:?}",
+                    mpid,
+                    position.primary_market_maker,
+                    position.market_maker_mode,
+                    position.market_participant_state
+                );
+            }
+
+            for (title, quoting) in [("Sell", asks), ("Buy", bids)] {
+                println!("----- {} Quoting -----", title);
+                for (price, mpids) in quoting {
+                    let mpids: Vec<String> = mpids.iter().map(|mpid| mpid.to_string()).collect();
+                    println!("{}: {}", price, mpids.join(" "));
+                }
+            }
+        }
+    }
+
+    pub fn display_events(&self) {
+        println!("----- Events -----");
+        for event in self.events.iter() {
+            match event {
+                Event::MwcbBreach { timestamp, level } => {
+                    println!("[{}] MWCB breach {:?}", timestamp, level)
+                }
+            }
+        }
+    }
+
+    pub fn display_directory(&self) {
+        println!("----- Directory -----");
+        for dir in self.directory() {
+            println!(
+                "[{}] {} {:?} {:?} {:?} {:?} round lot: {}{} {:?} {:?} {:?} {:?} {:?} leverage: {}{}",
+                dir.stock_locate,
+                dir.stock,
+                dir.market_category,
+                dir.financial_status,
+                dir.issue_classification,
+                dir.issue_subtype,
+                dir.round_lot_size,
+                if dir.round_lots_only { " only" } else { "" },
+                dir.authenticity,
+                dir.short_sale_threshold,
+                dir.ipo_flag,
+                dir.luld_ref_price_tier,
+                dir.etp_flag,
+                dir.etp_leverage_factor,
+                if dir.inverse_indicator { " inverse" } else { "" }
+            );
+        }
+    }
+}
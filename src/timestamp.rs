@@ -0,0 +1,276 @@
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::error::ParseError;
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+const NANOS_PER_HOUR: u64 = 3600 * NANOS_PER_SECOND;
+const NANOS_PER_DAY: u64 = 24 * NANOS_PER_HOUR;
+
+fn parse_digits(value: &str) -> Option<u64> {
+    if !value.is_empty() && value.bytes().all(|byte| byte.is_ascii_digit()) {
+        value.parse().ok()
+    } else {
+        None
+    }
+}
+
+// Splits "SS.nnnnnnnnn" into whole units and nanoseconds.
+fn parse_fraction(value: &str) -> Option<(u64, u64)> {
+    let (whole, fraction) = match value.split_once('.') {
+        Some((_, "")) => return None,
+        Some(parts) => parts,
+        None => (value, ""),
+    };
+    if fraction.len() > 9 || !fraction.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    Some((
+        parse_digits(whole)?,
+        format!("{:0<9}", fraction).parse().ok()?,
+    ))
+}
+
+// Nanoseconds since midnight Eastern time, as carried by every ITCH message.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct Timestamp(pub u64);
+
+impl Timestamp {
+    pub fn from_seconds(seconds: u64) -> Self {
+        Timestamp(seconds * NANOS_PER_SECOND)
+    }
+
+    pub fn seconds(self) -> u64 {
+        self.0 / NANOS_PER_SECOND
+    }
+
+    // The time elapsed since midnight is added to midnight of the trade date,
+    // whose UTC offset follows the US/Eastern daylight saving rules.
+    pub fn to_utc(self, date: TradeDate) -> UtcTime {
+        let offset = if date.is_daylight_saving() { 4 } else { 5 };
+        UtcTime(date.days_since_epoch() * NANOS_PER_DAY + offset * NANOS_PER_HOUR + self.0)
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let seconds = self.0 / NANOS_PER_SECOND;
+        write!(
+            f,
+            "{:02}:{:02}:{:02}.{:09}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60,
+            self.0 % NANOS_PER_SECOND
+        )
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = ParseError;
+
+    fn from_str(value: &str) -> Result<Self, ParseError> {
+        let error = || ParseError::new("timestamp", value);
+        let mut parts = value.splitn(3, ':');
+        let (Some(hours), Some(minutes), Some(seconds)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(error());
+        };
+        let hours = parse_digits(hours).ok_or_else(error)?;
+        let minutes = parse_digits(minutes).ok_or_else(error)?;
+        let (seconds, nanoseconds) = parse_fraction(seconds).ok_or_else(error)?;
+        if hours > 23 || minutes > 59 || seconds > 59 {
+            return Err(error());
+        }
+        Ok(Timestamp(
+            (hours * 3600 + minutes * 60 + seconds) * NANOS_PER_SECOND + nanoseconds,
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TradeDate {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+impl TradeDate {
+    pub fn new(year: u16, month: u8, day: u8) -> Option<Self> {
+        let days_in_month = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if year.is_multiple_of(4)
+                && (!year.is_multiple_of(100) || year.is_multiple_of(400)) =>
+            {
+                29
+            }
+            2 => 28,
+            _ => return None,
+        };
+        if !(1970..=9999).contains(&year) || day == 0 || day > days_in_month {
+            return None;
+        }
+        Some(TradeDate { year, month, day })
+    }
+
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    // Nasdaq names its files after the trade date as MMDDYYYY, as in
+    // 01302019.NASDAQ_ITCH50. ISO dates, with or without dashes, are
+    // recognised as well.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        let iso = (0..name.len())
+            .filter_map(|start| name.get(start..start + 10))
+            .find_map(|window| window.parse().ok());
+        iso.or_else(|| {
+            name.split(|c: char| !c.is_ascii_digit())
+                .filter(|digits| digits.len() == 8)
+                .find_map(|digits| {
+                    digits.parse().ok().or_else(|| {
+                        TradeDate::new(
+                            digits[4..].parse().ok()?,
+                            digits[..2].parse().ok()?,
+                            digits[2..4].parse().ok()?,
+                        )
+                    })
+                })
+        })
+    }
+
+    // Days from civil, see http://howardhinnant.github.io/date_algorithms.html
+    fn days_since_epoch(&self) -> u64 {
+        let year = self.year as i64 - (self.month <= 2) as i64;
+        let era = year / 400;
+        let year_of_era = year - era * 400;
+        let month = self.month as i64;
+        let day_of_year =
+            (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        (era * 146097 + day_of_era - 719468) as u64
+    }
+
+    fn from_days_since_epoch(days: u64) -> Self {
+        let days = days as i64 + 719468;
+        let era = days / 146097;
+        let day_of_era = days - era * 146097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+        let month = if month_index < 10 {
+            month_index + 3
+        } else {
+            month_index - 9
+        };
+        TradeDate {
+            year: (year_of_era + era * 400 + (month <= 2) as i64) as u16,
+            month: month as u8,
+            day: day as u8,
+        }
+    }
+
+    // Day of the month of the nth Sunday, or the last one when nth is 0.
+    fn sunday(year: u16, month: u8, nth: u8) -> u8 {
+        let first = TradeDate::new(year, month, 1).unwrap();
+        // 1970-01-01 was a Thursday.
+        let weekday = (first.days_since_epoch() + 4) % 7;
+        let first_sunday = 1 + ((7 - weekday) % 7) as u8;
+        if nth > 0 {
+            return first_sunday + 7 * (nth - 1);
+        }
+        let mut day = first_sunday;
+        while TradeDate::new(year, month, day + 7).is_some() {
+            day += 7;
+        }
+        day
+    }
+
+    // Whether midnight of the trade date falls in daylight saving time: it
+    // starts at 2:00 on the second Sunday of March and ends at 2:00 on the
+    // first Sunday of November, or the first Sunday of April and the last
+    // Sunday of October before 2007.
+    pub fn is_daylight_saving(&self) -> bool {
+        let (start, end) = if self.year >= 2007 {
+            (
+                (3, TradeDate::sunday(self.year, 3, 2)),
+                (11, TradeDate::sunday(self.year, 11, 1)),
+            )
+        } else {
+            (
+                (4, TradeDate::sunday(self.year, 4, 1)),
+                (10, TradeDate::sunday(self.year, 10, 0)),
+            )
+        };
+        let date = (self.month, self.day);
+        date > start && date <= end
+    }
+}
+
+impl fmt::Display for TradeDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for TradeDate {
+    type Err = ParseError;
+
+    fn from_str(value: &str) -> Result<Self, ParseError> {
+        let digits: String = value.chars().filter(|c| *c != '-').collect();
+        let dashes = value.len() - digits.len();
+        if digits.len() != 8
+            || !digits.bytes().all(|byte| byte.is_ascii_digit())
+            || (dashes != 0 && (dashes != 2 || &value[4..5] != "-" || &value[7..8] != "-"))
+        {
+            return Err(ParseError::new("date", value));
+        }
+        TradeDate::new(
+            digits[..4].parse().unwrap(),
+            digits[4..6].parse().unwrap(),
+            digits[6..].parse().unwrap(),
+        )
+        .ok_or_else(|| ParseError::new("date", value))
+    }
+}
+
+// Nanoseconds since the Unix epoch, the clock of capture timestamps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct UtcTime(pub u64);
+
+impl UtcTime {
+    // Seconds since the epoch with an optional fraction, kept exact to the
+    // nanosecond.
+    pub fn from_epoch(value: &str) -> Result<Self, ParseError> {
+        let error = || ParseError::new("epoch time", value);
+        let (seconds, nanoseconds) = parse_fraction(value).ok_or_else(error)?;
+        seconds
+            .checked_mul(NANOS_PER_SECOND)
+            .and_then(|seconds| seconds.checked_add(nanoseconds))
+            .map(UtcTime)
+            .ok_or_else(error)
+    }
+}
+
+impl fmt::Display for UtcTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let date = TradeDate::from_days_since_epoch(self.0 / NANOS_PER_DAY);
+        write!(f, "{}T{}Z", date, Timestamp(self.0 % NANOS_PER_DAY))
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::bookmanager::OrderManager;
+use crate::enums::CrossType;
+use crate::messages::{Body, Message};
+use crate::price::Price4;
+use crate::timestamp::Timestamp;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Trade {
+    pub timestamp: Timestamp,
+    pub price: Price4,
+    pub shares: u64,
+    pub match_number: u64,
+    pub printable: bool,
+    pub cross_type: Option<CrossType>,
+    pub capture_timestamp: Option<u64>,
+}
+
+#[derive(Debug)]
+pub struct TradeManager {
+    trades_per_stock_locate: HashMap<u16, Vec<Trade>>,
+}
+
+impl Default for TradeManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TradeManager {
+    pub fn new() -> Self {
+        TradeManager {
+            trades_per_stock_locate: HashMap::new(),
+        }
+    }
+
+    pub fn merge(&mut self, other: TradeManager) {
+        self.trades_per_stock_locate
+            .extend(other.trades_per_stock_locate);
+    }
+
+    pub fn trades(&self, stock_locate: u16) -> &[Trade] {
+        self.trades_per_stock_locate
+            .get(&stock_locate)
+            .map_or(&[], |trades| trades.as_slice())
+    }
+
+    fn add_trade(&mut self, stock_locate: u16, trade: Trade) {
+        self.trades_per_stock_locate
+            .entry(stock_locate)
+            .or_default()
+            .push(trade);
+    }
+
+    pub fn break_trade(&mut self, stock_locate: u16, match_number: u64) {
+        if let Some(trades) = self.trades_per_stock_locate.get_mut(&stock_locate) {
+            trades.retain(|trade| trade.match_number != match_number);
+        }
+    }
+
+    // Must be called before the order manager processes the message so that
+    // the resting price of executed orders is still known.
+    pub fn process(
+        &mut self,
+        message: &Message,
+        order_manager: &OrderManager,
+        capture_timestamp: Option<u64>,
+    ) {
+        match &message.body {
+            Body::ExecutedOrder(exec) => {
+                if let Some(price) = order_manager.order_price(exec.stock_locate, exec.reference) {
+                    self.add_trade(
+                        exec.stock_locate,
+                        Trade {
+                            timestamp: exec.timestamp,
+                            price,
+                            shares: exec.executed_shares as u64,
+                            match_number: exec.match_number,
+                            printable: true,
+                            cross_type: None,
+                            capture_timestamp,
+                        },
+                    );
+                }
+            }
+            Body::ExecutedWithPriceOrder(exec) => self.add_trade(
+                exec.stock_locate,
+                Trade {
+                    timestamp: exec.timestamp,
+                    price: exec.price,
+                    shares: exec.executed_shares as u64,
+                    match_number: exec.match_number,
+                    printable: exec.printable,
+                    cross_type: None,
+                    capture_timestamp,
+                },
+            ),
+            Body::NonCrossTrade(trade) => self.add_trade(
+                trade.stock_locate,
+                Trade {
+                    timestamp: trade.timestamp,
+                    price: trade.price,
+                    shares: trade.shares as u64,
+                    match_number: trade.match_number,
+                    printable: true,
+                    cross_type: None,
+                    capture_timestamp,
+                },
+            ),
+            Body::CrossTrade(cross) => self.add_trade(
+                cross.stock_locate,
+                Trade {
+                    timestamp: cross.timestamp,
+                    price: cross.cross_price,
+                    shares: cross.shares,
+                    match_number: cross.match_number,
+                    printable: true,
+                    cross_type: Some(cross.cross_type),
+                    capture_timestamp,
+                },
+            ),
+            Body::BrokenTrade(broken) => self.break_trade(broken.stock_locate, broken.match_number),
+            _ => (),
+        }
+    }
+
+    pub fn display_trades_csv(&self, stock_locate: u16) {
+        println!(
+            "timestamp,timestamp_ns,price,shares,match_number,printable,cross_type,capture_timestamp"
+        );
+        for trade in self.trades(stock_locate) {
+            println!(
+                "{},{},{},{},{},{},{},{}",
+                trade.timestamp,
+                trade.timestamp.0,
+                trade.price,
+                trade.shares,
+                trade.match_number,
+                trade.printable,
+                trade
+                    .cross_type
+                    .map_or(String::new(), |cross_type| format!("{:?}", cross_type)),
+                trade
+                    .capture_timestamp
+                    .map_or(String::new(), |timestamp| timestamp.to_string())
+            );
+        }
+    }
+
+    // One object per line, as the other streams.
+    pub fn display_trades_json(&self, stock_locate: u16) {
+        for trade in self.trades(stock_locate) {
+            println!("{}", serde_json::to_string(trade).unwrap());
+        }
+    }
+}
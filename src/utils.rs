@@ -1,3 +1,5 @@
+use crate::error::DecodeError;
+
 pub const UDP_DEST_PORT: u16 = 26477;
 pub const UDP_DEST_PORT_OFFSET: usize = 36;
 pub const PACKET_HEADER_OFFSET: usize = 42;
@@ -33,3 +35,38 @@ pub fn as_u32(bytes: &[u8]) -> u32 {
 pub fn as_u16(bytes: &[u8]) -> u16 {
     ((bytes[0] as u16) << 8) + bytes[1] as u16
 }
+
+pub fn as_flag(byte: u8) -> Result<bool, DecodeError> {
+    match byte {
+        b'Y' => Ok(true),
+        b'N' => Ok(false),
+        _ => Err(DecodeError::InvalidValue(byte)),
+    }
+}
+
+pub fn put_u64(bytes: &mut Vec<u8>, value: u64) {
+    bytes.extend_from_slice(&value.to_be_bytes());
+}
+
+pub fn put_u48(bytes: &mut Vec<u8>, value: u64) {
+    bytes.extend_from_slice(&value.to_be_bytes()[2..]);
+}
+
+pub fn put_u32(bytes: &mut Vec<u8>, value: u32) {
+    bytes.extend_from_slice(&value.to_be_bytes());
+}
+
+pub fn put_u16(bytes: &mut Vec<u8>, value: u16) {
+    bytes.extend_from_slice(&value.to_be_bytes());
+}
+
+pub fn put_flag(bytes: &mut Vec<u8>, value: bool) {
+    bytes.push(if value { b'Y' } else { b'N' });
+}
+
+pub fn put_alpha(bytes: &mut Vec<u8>, value: &str, length: usize) {
+    let value = value.as_bytes();
+    for i in 0..length {
+        bytes.push(*value.get(i).unwrap_or(&b' '));
+    }
+}
@@ -0,0 +1,165 @@
+use std::io::{self, Write};
+
+use crate::messages::{Message, PacketBuilder};
+use crate::timestamp::TradeDate;
+use crate::utils::{put_u16, PACKET_HEADER_OFFSET, UDP_DEST_PORT};
+
+const SESSION: &str = "SYNTHETIC";
+const SOURCE_IP: [u8; 4] = [10, 0, 0, 1];
+const DEST_IP: [u8; 4] = [233, 54, 12, 111];
+const UDP_SOURCE_PORT: u16 = 26400;
+const SNAPLEN: usize = 65535;
+
+// Largest MoldUDP64 packet whose frame fits the snapshot length, which also
+// keeps the IP and UDP lengths within 16 bits.
+pub const MAX_PAYLOAD: usize = SNAPLEN - PACKET_HEADER_OFFSET;
+
+pub fn udp_frame(payload: &[u8]) -> io::Result<Vec<u8>> {
+    let ip_length = match u16::try_from(20 + 8 + payload.len()) {
+        Ok(ip_length) if payload.len() <= MAX_PAYLOAD => ip_length,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} byte payload does not fit a UDP frame", payload.len()),
+            ))
+        }
+    };
+    let mut frame = Vec::with_capacity(PACKET_HEADER_OFFSET + payload.len());
+
+    // Ethernet
+    frame.extend_from_slice(&[0x01, 0x00, 0x5e, 0x36, 0x0c, 0x6f]);
+    frame.extend_from_slice(&[0x02, 0x00, 0x00, 0x00, 0x00, 0x01]);
+    put_u16(&mut frame, 0x0800);
+
+    // IPv4
+    let ip_start = frame.len();
+    frame.extend_from_slice(&[0x45, 0x00]);
+    put_u16(&mut frame, ip_length);
+    frame.extend_from_slice(&[0x00, 0x00, 0x40, 0x00, 0x40, 0x11, 0x00, 0x00]);
+    frame.extend_from_slice(&SOURCE_IP);
+    frame.extend_from_slice(&DEST_IP);
+    let checksum = ipv4_checksum(&frame[ip_start..]);
+    frame[ip_start + 10..ip_start + 12].copy_from_slice(&checksum.to_be_bytes());
+
+    // UDP, checksum left empty as allowed over IPv4
+    put_u16(&mut frame, UDP_SOURCE_PORT);
+    put_u16(&mut frame, UDP_DEST_PORT);
+    put_u16(&mut frame, ip_length - 20);
+    put_u16(&mut frame, 0);
+
+    frame.extend_from_slice(payload);
+    Ok(frame)
+}
+
+fn ipv4_checksum(header: &[u8]) -> u16 {
+    let mut sum: u32 = header
+        .chunks(2)
+        .map(|word| ((word[0] as u32) << 8) + word[1] as u32)
+        .sum();
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+#[derive(Debug)]
+pub struct PcapWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> PcapWriter<W> {
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        let mut header = Vec::with_capacity(24);
+        header.extend_from_slice(&0xa1b2c3d4u32.to_le_bytes());
+        header.extend_from_slice(&2u16.to_le_bytes());
+        header.extend_from_slice(&4u16.to_le_bytes());
+        header.extend_from_slice(&0i32.to_le_bytes());
+        header.extend_from_slice(&0u32.to_le_bytes());
+        header.extend_from_slice(&(SNAPLEN as u32).to_le_bytes());
+        header.extend_from_slice(&1u32.to_le_bytes());
+        writer.write_all(&header)?;
+        Ok(PcapWriter { writer })
+    }
+
+    // The timestamp counts nanoseconds since the epoch.
+    pub fn write_packet(&mut self, timestamp: u64, payload: &[u8]) -> io::Result<()> {
+        let frame = udp_frame(payload)?;
+        let mut record = Vec::with_capacity(16);
+        record.extend_from_slice(&((timestamp / 1_000_000_000) as u32).to_le_bytes());
+        record.extend_from_slice(&((timestamp % 1_000_000_000 / 1000) as u32).to_le_bytes());
+        record.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        record.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        self.writer.write_all(&record)?;
+        self.writer.write_all(&frame)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[derive(Debug)]
+pub struct ItchWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> ItchWriter<W> {
+    pub fn new(writer: W) -> Self {
+        ItchWriter { writer }
+    }
+
+    pub fn write_message(&mut self, message: &Message) -> io::Result<()> {
+        self.writer.write_all(&message.encode())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+// Packets hold up to batch_size messages, fewer when they would not fit a
+// UDP frame, and are stamped with the time of their first message on the
+// trade date.
+pub fn write_pcap<W: Write>(
+    writer: W,
+    messages: impl IntoIterator<Item = Message>,
+    batch_size: usize,
+    trade_date: TradeDate,
+) -> io::Result<W> {
+    let batch_size = batch_size.clamp(1, u16::MAX as usize);
+    let mut pcap = PcapWriter::new(writer)?;
+    let mut builder = PacketBuilder::new(SESSION, 1);
+    let mut sequence_number = 1;
+    let mut timestamp = 0;
+
+    for message in messages {
+        if !builder.is_empty() && builder.len() + message.length as usize + 2 > MAX_PAYLOAD {
+            pcap.write_packet(timestamp, &builder.build())?;
+            builder.clear(sequence_number);
+        }
+        if builder.is_empty() {
+            timestamp = message.body.timestamp().to_utc(trade_date).0;
+        }
+        builder.add(&message);
+        sequence_number += 1;
+        if builder.message_count() >= batch_size {
+            pcap.write_packet(timestamp, &builder.build())?;
+            builder.clear(sequence_number);
+        }
+    }
+    if !builder.is_empty() {
+        pcap.write_packet(timestamp, &builder.build())?;
+    }
+    Ok(pcap.into_inner())
+}
+
+pub fn write_itch<W: Write>(
+    writer: W,
+    messages: impl IntoIterator<Item = Message>,
+) -> io::Result<W> {
+    let mut itch = ItchWriter::new(writer);
+    for message in messages {
+        itch.write_message(&message)?;
+    }
+    Ok(itch.into_inner())
+}
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use bookbuilder::enums::Side;
+use bookbuilder::generator::{Generator, GeneratorConfig};
+use bookbuilder::messages::Body;
+use bookbuilder::price::Price4;
+
+// Live orders of a generated feed by reference: stock locate, side, price and
+// remaining shares.
+type LiveOrders = HashMap<u64, (u16, Side, Price4, u32)>;
+
+fn feed() -> Generator {
+    Generator::new(GeneratorConfig {
+        symbols: vec![String::from("AAPL"), String::from("MSFT")],
+        message_count: 5000,
+        ..GeneratorConfig::default()
+    })
+}
+
+fn best(orders: &LiveOrders, stock_locate: u16, side: Side) -> Option<Price4> {
+    let prices = orders
+        .values()
+        .filter(|(locate, order_side, _, _)| *locate == stock_locate && *order_side == side)
+        .map(|(_, _, price, _)| *price);
+    match side {
+        Side::Buy => prices.max(),
+        Side::Sell => prices.min(),
+    }
+}
+
+fn reduce(orders: &mut LiveOrders, reference: u64, shares: u32) {
+    let order = orders.get_mut(&reference).unwrap();
+    order.3 -= shares;
+    if order.3 == 0 {
+        orders.remove(&reference);
+    }
+}
+
+fn apply(orders: &mut LiveOrders, body: &Body) {
+    match body {
+        Body::AddOrder(add) => {
+            orders.insert(
+                add.reference,
+                (add.stock_locate, add.side, add.price, add.shares),
+            );
+        }
+        Body::ExecutedOrder(executed) => {
+            reduce(orders, executed.reference, executed.executed_shares)
+        }
+        Body::CancelOrder(cancel) => reduce(orders, cancel.reference, cancel.canceled_shares),
+        Body::DeleteOrder(delete) => {
+            orders.remove(&delete.reference);
+        }
+        Body::ReplaceOrder(replace) => {
+            let (stock_locate, side, _, _) = orders.remove(&replace.original_reference).unwrap();
+            orders.insert(
+                replace.new_reference,
+                (stock_locate, side, replace.price, replace.shares),
+            );
+        }
+        _ => (),
+    }
+}
+
+#[test]
+fn generated_executions_hit_the_touch() {
+    let mut orders = LiveOrders::new();
+    let mut executions = 0;
+    for message in feed() {
+        if let Body::ExecutedOrder(executed) = &message.body {
+            let (stock_locate, side, price, _) = orders[&executed.reference];
+            assert_eq!(best(&orders, stock_locate, side), Some(price));
+            executions += 1;
+        }
+        apply(&mut orders, &message.body);
+    }
+    assert!(executions > 0);
+}
+
+// The mid stays inside the spread, so new quotes spread out from it rather than
+// being clamped one tick away from the opposite side.
+#[test]
+fn generated_quotes_rest_away_from_the_opposite_touch() {
+    let mut orders = LiveOrders::new();
+    let mut quotes = 0;
+    let mut at_touch = 0;
+    for message in feed() {
+        if let Body::AddOrder(add) = &message.body {
+            let opposite = match add.side {
+                Side::Buy => Side::Sell,
+                Side::Sell => Side::Buy,
+            };
+            if let Some(price) = best(&orders, add.stock_locate, opposite) {
+                quotes += 1;
+                if price.0.abs_diff(add.price.0) == GeneratorConfig::default().tick_size {
+                    at_touch += 1;
+                }
+            }
+        }
+        apply(&mut orders, &message.body);
+    }
+    assert!(at_touch * 10 < quotes, "{} of {} quotes", at_touch, quotes);
+}
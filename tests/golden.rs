@@ -0,0 +1,520 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use bookbuilder::reader::{MmapReader, Reader};
+use bookbuilder::report::LevelUpdate;
+
+// Captures under tests/data are produced by itchgen, apart from all_types.itch
+// which is laid out by hand, set UPDATE_GOLDEN=1 to rewrite the expected
+// outputs after an intended change.
+fn data(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("data")
+        .join(name)
+}
+
+fn run(binary: &str, args: &[&str]) -> String {
+    let output = Command::new(binary)
+        .args(args)
+        .current_dir(data(""))
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{} {:?} failed: {}",
+        binary,
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn golden(args: &[&str], expected: &str) {
+    compare(run(env!("CARGO_BIN_EXE_bookbuilder"), args), expected);
+}
+
+fn compare(actual: String, expected: &str) {
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(data(expected), &actual).unwrap();
+    }
+    assert_eq!(
+        actual,
+        fs::read_to_string(data(expected)).unwrap(),
+        "{}",
+        expected
+    );
+}
+
+#[test]
+fn pcap_book_and_directory() {
+    golden(
+        &[
+            "-f",
+            "two_symbols.pcap",
+            "-s",
+            "aapl",
+            "-n",
+            "1000000",
+            "-d",
+            "5",
+            "-r",
+        ],
+        "aapl_book.txt",
+    );
+}
+
+#[test]
+fn itch_book_and_directory() {
+    golden(
+        &[
+            "-f",
+            "two_symbols.itch",
+            "-s",
+            "aapl",
+            "-n",
+            "1000000",
+            "-d",
+            "5",
+            "-r",
+        ],
+        "aapl_book.txt",
+    );
+}
+
+#[test]
+fn every_message_type_book_and_state() {
+    golden(
+        &[
+            "-f",
+            "all_types.itch",
+            "-s",
+            "aapl",
+            "-r",
+            "-e",
+            "-p",
+            "-a",
+            "-i",
+        ],
+        "all_types_aapl.txt",
+    );
+    golden(
+        &[
+            "-f",
+            "all_types.itch",
+            "-s",
+            "msft",
+            "-p",
+            "-a",
+            "-i",
+            "-o",
+            "json",
+        ],
+        "all_types_msft.json",
+    );
+    golden(
+        &["-f", "all_types.itch", "-s", "aapl", "-t", "csv"],
+        "all_types_aapl_trades.csv",
+    );
+}
+
+#[test]
+fn itch_book_through_buffered_reader() {
+    golden(
+        &[
+            "-f",
+            "two_symbols.itch",
+            "--reader",
+            "libpcap",
+            "-s",
+            "aapl",
+            "-n",
+            "1000000",
+            "-d",
+            "5",
+            "-r",
+        ],
+        "aapl_book.txt",
+    );
+}
+
+#[test]
+fn pcap_book_at_sequence_number() {
+    golden(
+        &[
+            "-f",
+            "two_symbols.pcap",
+            "-s",
+            "msft",
+            "-n",
+            "200",
+            "-d",
+            "5",
+        ],
+        "msft_book_200.txt",
+    );
+}
+
+#[test]
+fn pcap_book_at_capture_time() {
+    // The capture time of the last packet replayed by the sequence number stop.
+    let mut reader = MmapReader::new(&data("two_symbols.pcap")).unwrap();
+    let mut capture_time = 0;
+    while let Some(packet) = reader.next_packet() {
+        if packet.sequence_number > 200 {
+            break;
+        }
+        capture_time = packet.timestamp.unwrap();
+    }
+    let capture_time = format!(
+        "{}.{:09}",
+        capture_time / 1_000_000_000,
+        capture_time % 1_000_000_000
+    );
+
+    golden(
+        &[
+            "-f",
+            "two_symbols.pcap",
+            "-s",
+            "msft",
+            "-c",
+            &capture_time,
+            "-d",
+            "5",
+        ],
+        "msft_book_200.txt",
+    );
+}
+
+#[test]
+fn pcap_book_json() {
+    golden(
+        &[
+            "-f",
+            "two_symbols.pcap",
+            "-s",
+            "msft",
+            "-n",
+            "200",
+            "-d",
+            "3",
+            "-o",
+            "json",
+            "-p",
+            "-a",
+            "-i",
+        ],
+        "msft_book_200.json",
+    );
+}
+
+#[test]
+fn sharded_json_snapshots_match_single_symbol_replays() {
+    let binary = env!("CARGO_BIN_EXE_bookbuilder");
+    let all = run(
+        binary,
+        &[
+            "-f",
+            "two_symbols.pcap",
+            "--all",
+            "-o",
+            "json",
+            "-j",
+            "2",
+            "-r",
+        ],
+    );
+    let mut lines = all.lines();
+    let market: serde_json::Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+    assert_eq!(market["type"], "market");
+    assert_eq!(market["directory"].as_array().unwrap().len(), 2);
+
+    let mut symbols = 0;
+    for line in lines {
+        let snapshot: serde_json::Value = serde_json::from_str(line).unwrap();
+        let symbol = snapshot["symbol"].as_str().unwrap();
+        let single = run(
+            binary,
+            &["-f", "two_symbols.pcap", "-s", symbol, "-o", "json"],
+        );
+        assert_eq!(single.lines().count(), 1);
+        assert_eq!(
+            snapshot,
+            serde_json::from_str::<serde_json::Value>(&single).unwrap(),
+            "{}",
+            symbol
+        );
+        symbols += 1;
+    }
+    assert_eq!(symbols, 2);
+}
+
+#[test]
+fn pcap_trades_csv() {
+    golden(
+        &[
+            "-f",
+            "two_symbols.pcap",
+            "-s",
+            "aapl",
+            "-n",
+            "1000000",
+            "-t",
+            "csv",
+        ],
+        "aapl_trades.csv",
+    );
+}
+
+#[test]
+fn itch_trades_json() {
+    golden(
+        &[
+            "-f",
+            "two_symbols.itch",
+            "-s",
+            "msft",
+            "-n",
+            "1000000",
+            "-t",
+            "json",
+        ],
+        "msft_trades.ndjson",
+    );
+}
+
+// Exports into a temporary directory named after the test and format, so that
+// tests running in parallel do not share one.
+fn export(name: &str, args: &[&str], format: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!(
+        "bookbuilder-export-{}-{}-{}",
+        name,
+        format,
+        std::process::id()
+    ));
+    let mut args = args.to_vec();
+    args.extend(["-x", dir.to_str().unwrap(), "--export-format", format]);
+    run(env!("CARGO_BIN_EXE_bookbuilder"), &args);
+    dir
+}
+
+#[test]
+fn every_message_type_export_csv() {
+    let dir = export("all_types", &["-f", "all_types.itch", "--all"], "csv");
+    compare(
+        fs::read_to_string(dir.join("events.csv")).unwrap(),
+        "all_types_events_export.csv",
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn unknown_messages_export_raw_bytes() {
+    let dir = env::temp_dir().join(format!("bookbuilder-unknown-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let itch = dir.join("unknown.itch");
+    // A 'G' message, which ITCH 5.0 does not define, with two bytes of body.
+    fs::write(
+        &itch,
+        [0, 13, b'G', 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0xab, 0xcd],
+    )
+    .unwrap();
+    let export = dir.join("export");
+    run(
+        env!("CARGO_BIN_EXE_bookbuilder"),
+        &[
+            "-f",
+            itch.to_str().unwrap(),
+            "--all",
+            "-x",
+            export.to_str().unwrap(),
+        ],
+    );
+    let events = fs::read_to_string(export.join("events.csv")).unwrap();
+    let row = events.lines().nth(1).unwrap();
+    assert!(row.starts_with("1,,1,G,0,0,"), "{}", row);
+    assert!(row.ends_with(",00000000000000000001abcd"), "{}", row);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn pcap_export_csv() {
+    let dir = export(
+        "aapl",
+        &[
+            "-f",
+            "two_symbols.pcap",
+            "-s",
+            "aapl",
+            "-d",
+            "3",
+            "--interval",
+            "1",
+        ],
+        "csv",
+    );
+    for table in ["snapshots", "trades", "events"] {
+        compare(
+            fs::read_to_string(dir.join(format!("{}.csv", table))).unwrap(),
+            &format!("aapl_{}_export.csv", table),
+        );
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+// Every Parquet table holds the same rows as its CSV counterpart, prices as
+// exact decimals.
+#[cfg(feature = "parquet")]
+#[test]
+fn parquet_export_matches_csv() {
+    use parquet::file::reader::SerializedFileReader;
+    use parquet::record::Field;
+
+    for file in ["two_symbols.itch", "all_types.itch"] {
+        let args = ["-f", file, "--all", "-d", "2"];
+        let csv = export(file, &args, "csv");
+        let parquet = export(file, &args, "parquet");
+        for table in ["snapshots", "trades", "events"] {
+            let expected = fs::read_to_string(csv.join(format!("{}.csv", table))).unwrap();
+            let mut expected = expected.lines();
+            let header: Vec<&str> = expected.next().unwrap().split(',').collect();
+            let file = fs::File::open(parquet.join(format!("{}.parquet", table))).unwrap();
+            let rows: Vec<_> = SerializedFileReader::new(file)
+                .unwrap()
+                .into_iter()
+                .map(Result::unwrap)
+                .collect();
+            assert!(!rows.is_empty(), "{}", table);
+
+            for (row, line) in rows.iter().zip(expected.by_ref()) {
+                let names: Vec<&str> = row
+                    .get_column_iter()
+                    .map(|(name, _)| name.as_str())
+                    .collect();
+                assert_eq!(names, header, "{}", table);
+                let cells = row.get_column_iter().map(|(_, field)| match field {
+                    Field::Null => String::new(),
+                    Field::Bool(value) => value.to_string(),
+                    Field::ULong(value) => value.to_string(),
+                    Field::Decimal(decimal) => {
+                        let value = i64::from_be_bytes(decimal.data().try_into().unwrap());
+                        let scale = 10i64.pow(decimal.scale() as u32);
+                        format!(
+                            "{}.{:0width$}",
+                            value / scale,
+                            value % scale,
+                            width = decimal.scale() as usize
+                        )
+                    }
+                    Field::Str(value) if value.contains(',') => format!("\"{}\"", value),
+                    Field::Str(value) => value.clone(),
+                    field => panic!("unexpected field {:?}", field),
+                });
+                assert_eq!(cells.collect::<Vec<_>>().join(","), line, "{}", table);
+            }
+            assert_eq!(expected.next(), None, "{}", table);
+        }
+        fs::remove_dir_all(&csv).unwrap();
+        fs::remove_dir_all(&parquet).unwrap();
+    }
+}
+
+#[test]
+fn pcap_bbo_csv() {
+    golden(
+        &["-f", "two_symbols.pcap", "-s", "aapl", "-b", "csv"],
+        "aapl_bbo.csv",
+    );
+}
+
+#[test]
+fn itch_bbo_json() {
+    golden(
+        &["-f", "two_symbols.itch", "--all", "-b", "json"],
+        "bbo.ndjson",
+    );
+}
+
+#[test]
+fn itch_deltas_json() {
+    golden(
+        &["-f", "two_symbols.itch", "-s", "msft", "--deltas", "json"],
+        "msft_deltas.ndjson",
+    );
+}
+
+#[test]
+fn binary_deltas_match_json() {
+    let binary = env!("CARGO_BIN_EXE_bookbuilder");
+    let output = Command::new(binary)
+        .args(["-f", "two_symbols.pcap", "--all", "--deltas", "binary"])
+        .current_dir(data(""))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json = run(
+        binary,
+        &["-f", "two_symbols.pcap", "--all", "--deltas", "json"],
+    );
+
+    assert_eq!(output.stdout.len() % LevelUpdate::LENGTH, 0);
+    assert_eq!(
+        output.stdout.len() / LevelUpdate::LENGTH,
+        json.lines().count()
+    );
+    for (record, line) in output.stdout.chunks(LevelUpdate::LENGTH).zip(json.lines()) {
+        assert_eq!(LevelUpdate::decode(record).unwrap().to_json(), line);
+    }
+}
+
+#[test]
+fn generator_is_reproducible() {
+    let dir = env::temp_dir().join(format!("bookbuilder-golden-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let pcap = dir.join("two_symbols.pcap");
+    let itch = dir.join("two_symbols.itch");
+
+    run(
+        env!("CARGO_BIN_EXE_itchgen"),
+        &[
+            "-o",
+            pcap.to_str().unwrap(),
+            "-s",
+            "AAPL,MSFT",
+            "-m",
+            "400",
+            "--seed",
+            "7",
+            "-b",
+            "8",
+        ],
+    );
+    run(
+        env!("CARGO_BIN_EXE_itchgen"),
+        &[
+            "-o",
+            itch.to_str().unwrap(),
+            "-F",
+            "itch",
+            "-s",
+            "AAPL,MSFT",
+            "-m",
+            "400",
+            "--seed",
+            "7",
+        ],
+    );
+
+    assert_eq!(
+        fs::read(&pcap).unwrap(),
+        fs::read(data("two_symbols.pcap")).unwrap()
+    );
+    assert_eq!(
+        fs::read(&itch).unwrap(),
+        fs::read(data("two_symbols.itch")).unwrap()
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,521 @@
+use bookbuilder::enums::*;
+use bookbuilder::error::DecodeError;
+use bookbuilder::messages::*;
+use bookbuilder::price::{Price4, Price8};
+use bookbuilder::timestamp::Timestamp;
+
+// A length prefixed Stock Directory message laid out by hand from the ITCH 5.0
+// specification: AAPL at locate 1, 09:30:00, round lots of 100.
+fn stock_directory(market_category: u8, financial_status: u8) -> Vec<u8> {
+    let mut bytes = vec![0, 39, b'R', 0, 1, 0, 0];
+    bytes.extend_from_slice(&[0x1f, 0x1a, 0xce, 0xd9, 0xf0, 0x00]);
+    bytes.extend_from_slice(b"AAPL    ");
+    bytes.extend_from_slice(&[market_category, financial_status, 0, 0, 0, 100, b'N', b'C']);
+    bytes.extend_from_slice(b"C P N1N");
+    bytes.extend_from_slice(&[0, 0, 0, 0, b'N']);
+    bytes
+}
+
+#[test]
+fn stock_directory_market_categories() {
+    for (byte, market_category) in [
+        (b'Q', MarketCategory::NasdaqGlobalSelect),
+        (b'G', MarketCategory::NasdaqGlobalMarket),
+        (b'S', MarketCategory::NasdaqCapitalMarket),
+        (b'N', MarketCategory::Nyse),
+    ] {
+        let bytes = stock_directory(byte, b'N');
+        let message = Message::new(&bytes).unwrap();
+        match &message.body {
+            Body::StockDirectory(directory) => {
+                assert_eq!(directory.timestamp, Timestamp(34_200_000_000_000));
+                assert_eq!(directory.stock, Stock::from("AAPL"));
+                assert_eq!(directory.market_category, market_category);
+                assert_eq!(directory.financial_status, FinancialStatus::Normal);
+                assert_eq!(directory.round_lot_size, 100);
+                assert_eq!(
+                    directory.issue_classification,
+                    IssueClassification::CommonStock
+                );
+                assert_eq!(directory.issue_subtype, IssueSubType::CommonShares);
+            }
+            body => panic!("decoded {:?}", body),
+        }
+        assert_eq!(message.encode(), bytes);
+    }
+}
+
+#[test]
+fn stock_directory_financial_statuses() {
+    for (byte, financial_status) in [
+        (b'D', FinancialStatus::Deficient),
+        (b'E', FinancialStatus::Delinquent),
+        (b'Q', FinancialStatus::Bankrupt),
+        (b'S', FinancialStatus::Suspended),
+        (b'G', FinancialStatus::DeficientBankrupt),
+        (b'H', FinancialStatus::DeficientDelinquent),
+        (b'J', FinancialStatus::DelinquentBankrupt),
+        (b'K', FinancialStatus::DeficientDelinquentBankrupt),
+        (b'C', FinancialStatus::EtpSuspended),
+        (b'N', FinancialStatus::Normal),
+        (b' ', FinancialStatus::Unavailable),
+    ] {
+        let bytes = stock_directory(b'Q', byte);
+        match Message::new(&bytes).unwrap().body {
+            Body::StockDirectory(directory) => {
+                assert_eq!(directory.financial_status, financial_status)
+            }
+            body => panic!("decoded {:?}", body),
+        }
+        assert_eq!(financial_status.to_byte(), byte);
+    }
+}
+
+#[test]
+fn replace_order_reads_shares_before_price() {
+    // Replace Order at locate 1, 09:30:00: order 7 becomes order 8 for 300
+    // shares at 190.0000.
+    let mut bytes = vec![0, 35, b'U', 0, 1, 0, 0];
+    bytes.extend_from_slice(&[0x1f, 0x1a, 0xce, 0xd9, 0xf0, 0x00]);
+    bytes.extend_from_slice(&7u64.to_be_bytes());
+    bytes.extend_from_slice(&8u64.to_be_bytes());
+    bytes.extend_from_slice(&300u32.to_be_bytes());
+    bytes.extend_from_slice(&1_900_000u32.to_be_bytes());
+    let message = Message::new(&bytes).unwrap();
+    assert_eq!(
+        message.body,
+        Body::ReplaceOrder(ReplaceOrder {
+            stock_locate: 1,
+            tracking_number: 0,
+            timestamp: Timestamp(34_200_000_000_000),
+            original_reference: 7,
+            new_reference: 8,
+            shares: 300,
+            price: Price4(1_900_000),
+        })
+    );
+    assert_eq!(message.encode(), bytes);
+}
+
+fn at(hours: u64, minutes: u64, seconds: u64) -> Timestamp {
+    Timestamp::from_seconds(hours * 3600 + minutes * 60 + seconds)
+}
+
+// tests/data/all_types.itch is laid out by hand from the ITCH 5.0
+// specification: AAPL at locate 1 trades through the day while MSFT at locate
+// 2 opens from an IPO halt.
+fn fixture() -> Vec<Vec<u8>> {
+    let data = std::fs::read("tests/data/all_types.itch").unwrap();
+    let mut messages = Vec::new();
+    let mut offset = 0;
+    while offset < data.len() {
+        let length = u16::from_be_bytes([data[offset], data[offset + 1]]) as usize;
+        messages.push(data[offset..offset + 2 + length].to_vec());
+        offset += 2 + length;
+    }
+    messages
+}
+
+#[test]
+fn fixture_decodes_every_message_type() {
+    let aapl = Stock::from("AAPL");
+    let msft = Stock::from("MSFT");
+    let expected = [
+        (
+            1,
+            Body::SystemEvent(SystemEvent {
+                stock_locate: 0,
+                tracking_number: 0,
+                timestamp: Timestamp(at(4, 0, 0).0 + 1),
+                event_code: SystemEventCode::StartOfSystemHours,
+            }),
+        ),
+        (
+            3,
+            Body::StockDirectory(StockDirectory {
+                stock_locate: 2,
+                tracking_number: 0,
+                timestamp: at(4, 0, 3),
+                stock: msft,
+                market_category: MarketCategory::NasdaqCapitalMarket,
+                financial_status: FinancialStatus::Deficient,
+                round_lot_size: 100,
+                round_lots_only: true,
+                issue_classification: IssueClassification::CommonStock,
+                issue_subtype: IssueSubType::CommonShares,
+                authenticity: Authenticity::Production,
+                short_sale_threshold: ThresholdIndicator::Restricted,
+                ipo_flag: IpoFlag::New,
+                luld_ref_price_tier: LuldRefPriceTier::Tier2,
+                etp_flag: EtpFlag::IsNot,
+                etp_leverage_factor: 0,
+                inverse_indicator: false,
+            }),
+        ),
+        (
+            5,
+            Body::StockTradingAction(StockTradingAction {
+                stock_locate: 2,
+                tracking_number: 0,
+                timestamp: at(4, 0, 5),
+                stock: msft,
+                trading_state: TradingState::Halted,
+                reason: Alpha::from("IPO1"),
+            }),
+        ),
+        (
+            6,
+            Body::RegShoRestriction(RegShoRestriction {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: at(4, 0, 6),
+                stock: aapl,
+                reg_sho_action: RegShoAction::RestrictionInEffect,
+            }),
+        ),
+        (
+            8,
+            Body::MarketParticipantPosition(MarketParticipantPosition {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: at(4, 0, 8),
+                mpid: Mpid::from("GSCO"),
+                stock: aapl,
+                primary_market_maker: false,
+                market_maker_mode: MarketMakerMode::Passive,
+                market_participant_state: MarketParticipantState::Excused,
+            }),
+        ),
+        (
+            9,
+            Body::MwcbDeclineLevel(MwcbDeclineLevel {
+                stock_locate: 0,
+                tracking_number: 0,
+                timestamp: at(7, 0, 0),
+                level1: Price8(440_000_000_000),
+                level2: Price8(410_000_000_000),
+                level3: Price8(370_000_000_000),
+            }),
+        ),
+        (
+            10,
+            Body::IpoQuotingPeriodUpdate(IpoQuotingPeriodUpdate {
+                stock_locate: 2,
+                tracking_number: 0,
+                timestamp: at(8, 0, 0),
+                stock: msft,
+                release_time: at(10, 30, 0),
+                release_qualifier: IpoReleaseQualifier::Anticipated,
+                ipo_price: Price4(250_000),
+            }),
+        ),
+        (
+            11,
+            Body::LuldAuctionCollar(LuldAuctionCollar {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: at(9, 29, 0),
+                stock: aapl,
+                reference_price: Price4(1_900_000),
+                upper_price: Price4(1_995_000),
+                lower_price: Price4(1_805_000),
+                extension: 1,
+            }),
+        ),
+        (
+            13,
+            Body::AddOrder(AddOrder {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: at(9, 30, 1),
+                reference: 1,
+                side: Side::Buy,
+                shares: 100,
+                stock: aapl,
+                price: Price4(1_900_000),
+                attribution: None,
+            }),
+        ),
+        (
+            14,
+            Body::AddOrder(AddOrder {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: at(9, 30, 2),
+                reference: 2,
+                side: Side::Sell,
+                shares: 200,
+                stock: aapl,
+                price: Price4(1_900_500),
+                attribution: Some(Mpid::from("NITE")),
+            }),
+        ),
+        (
+            15,
+            Body::ExecutedOrder(ExecutedOrder {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: at(9, 30, 3),
+                reference: 2,
+                executed_shares: 50,
+                match_number: 1,
+            }),
+        ),
+        (
+            16,
+            Body::ExecutedWithPriceOrder(ExecutedWithPriceOrder {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: at(9, 30, 4),
+                reference: 1,
+                executed_shares: 100,
+                match_number: 2,
+                printable: true,
+                price: Price4(1_899_900),
+            }),
+        ),
+        (
+            18,
+            Body::CancelOrder(CancelOrder {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: at(9, 30, 6),
+                reference: 3,
+                canceled_shares: 100,
+            }),
+        ),
+        (
+            19,
+            Body::ReplaceOrder(ReplaceOrder {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: at(9, 30, 7),
+                original_reference: 3,
+                new_reference: 4,
+                price: Price4(1_899_700),
+                shares: 250,
+            }),
+        ),
+        (
+            20,
+            Body::NonCrossTrade(NonCrossTrade {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: at(9, 30, 8),
+                reference: 0,
+                side: Side::Buy,
+                shares: 500,
+                stock: aapl,
+                price: Price4(1_900_100),
+                match_number: 3,
+            }),
+        ),
+        (
+            21,
+            Body::BrokenTrade(BrokenTrade {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: at(9, 30, 9),
+                match_number: 3,
+            }),
+        ),
+        (
+            22,
+            Body::RetailPriceImprovement(RetailPriceImprovement {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: at(9, 30, 10),
+                stock: aapl,
+                interest: RetailInterest::Both,
+            }),
+        ),
+        (
+            23,
+            Body::OperationalHalt(OperationalHalt {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: at(9, 31, 0),
+                stock: aapl,
+                market_code: MarketCode::Nasdaq,
+                action: OperationalHaltAction::Halted,
+            }),
+        ),
+        (
+            25,
+            Body::DirectListingPriceDiscovery(DirectListingPriceDiscovery {
+                stock_locate: 2,
+                tracking_number: 0,
+                timestamp: at(10, 29, 0),
+                stock: msft,
+                open_eligible: true,
+                min_allowable_price: Price4(200_000),
+                max_allowable_price: Price4(300_000),
+                near_execution_price: Price4(255_000),
+                near_execution_time: at(10, 30, 0),
+                lower_price_range_collar: Price4(240_000),
+                upper_price_range_collar: Price4(270_000),
+            }),
+        ),
+        (
+            28,
+            Body::MwcbStatus(MwcbStatus {
+                stock_locate: 0,
+                tracking_number: 0,
+                timestamp: at(14, 0, 0),
+                breached_level: MwcbLevel::Level1,
+            }),
+        ),
+        (
+            29,
+            Body::NetOrderImbalance(NetOrderImbalance {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: at(15, 50, 0),
+                paired_shares: 1000,
+                imbalance_shares: 200,
+                imbalance_direction: ImbalanceDirection::Buy,
+                stock: aapl,
+                far_price: Price4(1_901_000),
+                near_price: Price4(1_900_500),
+                current_reference_price: Price4(1_900_200),
+                cross_type: CrossType::Closing,
+                price_variation_indicator: PriceVariationIndicator::LessThan1Percent,
+            }),
+        ),
+        (
+            30,
+            Body::CrossTrade(CrossTrade {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: at(16, 0, 0),
+                shares: 10_000,
+                stock: aapl,
+                cross_price: Price4(1_900_200),
+                match_number: 4,
+                cross_type: CrossType::Closing,
+            }),
+        ),
+        (
+            31,
+            Body::DeleteOrder(DeleteOrder {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: at(16, 0, 1),
+                reference: 4,
+            }),
+        ),
+    ];
+
+    let fixture = fixture();
+    let messages: Vec<Message> = fixture
+        .iter()
+        .map(|bytes| Message::new(bytes).unwrap())
+        .collect();
+    let types: String = messages
+        .iter()
+        .map(|message| message.body.msg_type() as char)
+        .collect();
+    assert_eq!(types, "SSRRHHYLLVKJSAFECAXUPBNhhOHAWIQDSSS");
+    for (index, body) in expected {
+        assert_eq!(messages[index].body, body, "message {}", index);
+    }
+    for (message, bytes) in messages.iter().zip(fixture.iter()) {
+        assert_eq!(&message.encode(), bytes);
+    }
+}
+
+#[test]
+fn views_read_fields_from_the_buffer() {
+    for bytes in fixture() {
+        let view = MessageView::new(&bytes).unwrap();
+        let message = view.decode().unwrap();
+        let (reference, new_reference, side, shares, price, attribution) = match &message.body {
+            Body::AddOrder(add) => (
+                Some(add.reference),
+                None,
+                Some(add.side),
+                Some(add.shares),
+                Some(add.price),
+                add.attribution,
+            ),
+            Body::ExecutedOrder(executed) => (
+                Some(executed.reference),
+                None,
+                None,
+                Some(executed.executed_shares),
+                None,
+                None,
+            ),
+            Body::ExecutedWithPriceOrder(executed) => (
+                Some(executed.reference),
+                None,
+                None,
+                Some(executed.executed_shares),
+                Some(executed.price),
+                None,
+            ),
+            Body::CancelOrder(cancel) => (
+                Some(cancel.reference),
+                None,
+                None,
+                Some(cancel.canceled_shares),
+                None,
+                None,
+            ),
+            Body::DeleteOrder(delete) => (Some(delete.reference), None, None, None, None, None),
+            Body::ReplaceOrder(replace) => (
+                Some(replace.original_reference),
+                Some(replace.new_reference),
+                None,
+                Some(replace.shares),
+                Some(replace.price),
+                None,
+            ),
+            Body::NonCrossTrade(trade) => (
+                Some(trade.reference),
+                None,
+                Some(trade.side),
+                Some(trade.shares),
+                Some(trade.price),
+                None,
+            ),
+            Body::CrossTrade(trade) => (None, None, None, None, Some(trade.cross_price), None),
+            _ => (None, None, None, None, None, None),
+        };
+        let stock = match &message.body {
+            Body::AddOrder(msg) => Some(msg.stock),
+            Body::CrossTrade(msg) => Some(msg.stock),
+            Body::DirectListingPriceDiscovery(msg) => Some(msg.stock),
+            Body::IpoQuotingPeriodUpdate(msg) => Some(msg.stock),
+            Body::LuldAuctionCollar(msg) => Some(msg.stock),
+            Body::MarketParticipantPosition(msg) => Some(msg.stock),
+            Body::NetOrderImbalance(msg) => Some(msg.stock),
+            Body::NonCrossTrade(msg) => Some(msg.stock),
+            Body::OperationalHalt(msg) => Some(msg.stock),
+            Body::RegShoRestriction(msg) => Some(msg.stock),
+            Body::RetailPriceImprovement(msg) => Some(msg.stock),
+            Body::StockDirectory(msg) => Some(msg.stock),
+            Body::StockTradingAction(msg) => Some(msg.stock),
+            _ => None,
+        };
+        let msg_type = view.msg_type() as char;
+        assert_eq!(view.reference(), reference, "{}", msg_type);
+        assert_eq!(view.new_reference(), new_reference, "{}", msg_type);
+        assert_eq!(view.side(), Ok(side), "{}", msg_type);
+        assert_eq!(view.shares(), shares, "{}", msg_type);
+        assert_eq!(view.price(), price, "{}", msg_type);
+        assert_eq!(view.stock(), stock, "{}", msg_type);
+        assert_eq!(view.attribution(), attribution, "{}", msg_type);
+    }
+}
+
+#[test]
+fn empty_messages_are_truncated() {
+    assert_eq!(
+        MessageView::new(&[0, 0, 0]).unwrap_err(),
+        DecodeError::Truncated {
+            expected: 3,
+            actual: 2
+        }
+    );
+}
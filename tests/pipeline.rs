@@ -0,0 +1,418 @@
+use std::collections::{BTreeMap, HashMap};
+
+use bookbuilder::bookmanager::{Bbo, Level, PriceLevel};
+use bookbuilder::enums::{
+    LevelAction, MarketMakerMode, MarketParticipantState, RetailInterest, Side, TradingState,
+};
+use bookbuilder::generator::{Generator, GeneratorConfig, PriceProcess};
+use bookbuilder::messages::{
+    AddOrder, Alpha, Body, MarketParticipantPosition, Message, MessageView, Mpid,
+    RetailPriceImprovement, Stock, StockTradingAction,
+};
+use bookbuilder::parallel::replay_sharded;
+use bookbuilder::pipeline::Pipeline;
+use bookbuilder::price::Price4;
+use bookbuilder::reader::{ItchReader, StopCondition};
+use bookbuilder::report::{LevelUpdate, Participants, Report, Snapshot};
+use bookbuilder::timestamp::Timestamp;
+
+const SYMBOLS: [&str; 4] = ["AAPL", "MSFT", "AMZN", "NVDA"];
+
+fn feed() -> Vec<Vec<u8>> {
+    Generator::new(GeneratorConfig {
+        symbols: SYMBOLS.iter().map(|symbol| symbol.to_string()).collect(),
+        message_count: 5000,
+        ..GeneratorConfig::default()
+    })
+    .map(|message| message.encode())
+    .collect()
+}
+
+#[test]
+fn price_processes_keep_books_uncrossed() {
+    let processes = [
+        PriceProcess::RandomWalk,
+        PriceProcess::MeanReverting { strength: 0.05 },
+        PriceProcess::Jump {
+            probability: 0.01,
+            size: 20,
+        },
+    ];
+    let mut feeds = Vec::new();
+    for price_process in processes {
+        let feed: Vec<Vec<u8>> = Generator::new(GeneratorConfig {
+            symbols: SYMBOLS.iter().map(|symbol| symbol.to_string()).collect(),
+            message_count: 5000,
+            price_process,
+            ..GeneratorConfig::default()
+        })
+        .map(|message| message.encode())
+        .collect();
+
+        let mut pipeline = Pipeline::new();
+        pipeline.book_manager.track_bbo();
+        for bytes in feed.iter() {
+            pipeline.process(&Message::new(bytes).unwrap());
+            for (_, bbo) in pipeline.book_manager.bbo_changes() {
+                if let (Some(bid), Some(ask)) = (bbo.bid_price, bbo.ask_price) {
+                    assert!(bid < ask, "{:?} crossed at {:?}", price_process, bbo);
+                }
+            }
+        }
+        feeds.push(feed);
+    }
+    assert_ne!(feeds[0], feeds[1]);
+    assert_ne!(feeds[0], feeds[2]);
+}
+
+#[test]
+fn filtered_pipeline_matches_full_pipeline() {
+    let feed = feed();
+    let mut full = Pipeline::new();
+    for bytes in feed.iter() {
+        full.process(&Message::new(bytes).unwrap());
+    }
+
+    for symbol in SYMBOLS {
+        let mut filtered = Pipeline::with_symbols(&[symbol]);
+        for bytes in feed.iter() {
+            filtered
+                .process_view(&MessageView::new(bytes).unwrap())
+                .unwrap();
+        }
+
+        let stock_locate = full.stock_locate(symbol).unwrap();
+        assert_eq!(filtered.stock_locate(symbol), Some(stock_locate));
+        assert_eq!(
+            filtered
+                .book_manager
+                .books_per_stock_locate
+                .keys()
+                .collect::<Vec<_>>(),
+            vec![&stock_locate]
+        );
+        assert_eq!(
+            filtered.book_manager.books_per_stock_locate[&stock_locate],
+            full.book_manager.books_per_stock_locate[&stock_locate]
+        );
+        assert_eq!(
+            filtered.trade_manager.trades(stock_locate).len(),
+            full.trade_manager.trades(stock_locate).len()
+        );
+    }
+}
+
+#[test]
+fn filter_waits_for_locates_without_directory() {
+    // Without the directory the first add of the symbol reveals its locate.
+    let feed: Vec<Vec<u8>> = feed()
+        .into_iter()
+        .filter(|bytes| bytes[2] != b'R')
+        .collect();
+    let mut full = Pipeline::new();
+    let mut filtered = Pipeline::with_symbols(&["MSFT"]);
+    for bytes in feed.iter() {
+        full.process(&Message::new(bytes).unwrap());
+        filtered
+            .process_view(&MessageView::new(bytes).unwrap())
+            .unwrap();
+    }
+
+    let stock_locate = full.stock_locate("MSFT").unwrap();
+    assert_eq!(
+        filtered.book_manager.books_per_stock_locate[&stock_locate],
+        full.book_manager.books_per_stock_locate[&stock_locate]
+    );
+}
+
+#[test]
+fn sharded_replay_matches_sequential_replay() {
+    let feed = feed();
+    let mut sequential = Pipeline::new();
+    for bytes in feed.iter() {
+        sequential.process(&Message::new(bytes).unwrap());
+    }
+
+    let capture = feed.concat();
+    for threads in [1, 3] {
+        let mut reader = ItchReader::from_reader(capture.as_slice());
+        let sharded = replay_sharded(&mut reader, StopCondition::default(), threads);
+
+        assert_eq!(sharded.listing, sequential.listing);
+        assert_eq!(
+            sharded.book_manager.books_per_stock_locate,
+            sequential.book_manager.books_per_stock_locate
+        );
+        for stock_locate in sequential.listing.keys() {
+            assert_eq!(
+                sharded.trade_manager.trades(*stock_locate).len(),
+                sequential.trade_manager.trades(*stock_locate).len()
+            );
+        }
+    }
+}
+
+#[test]
+fn sharded_replay_stops_at_sequence_number() {
+    let feed = feed();
+    let mut sequential = Pipeline::new();
+    for bytes in feed.iter().take(1000) {
+        sequential.process(&Message::new(bytes).unwrap());
+    }
+
+    let capture = feed.concat();
+    let mut reader = ItchReader::from_reader(capture.as_slice());
+    let sharded = replay_sharded(
+        &mut reader,
+        StopCondition {
+            sequence_number: 1000,
+            ..StopCondition::default()
+        },
+        2,
+    );
+    assert_eq!(
+        sharded.book_manager.books_per_stock_locate,
+        sequential.book_manager.books_per_stock_locate
+    );
+}
+
+#[test]
+fn snapshot_reports_trading_state_and_order_counts() {
+    let mut pipeline = Pipeline::new();
+    let halt = Message::with_body(Body::StockTradingAction(StockTradingAction {
+        stock_locate: 1,
+        tracking_number: 0,
+        timestamp: Timestamp(34_200_000_000_000),
+        stock: Stock::from("AAPL"),
+        trading_state: TradingState::Halted,
+        reason: Alpha::from("LUDP"),
+    }));
+    pipeline
+        .process_view(&MessageView::new(&halt.encode()).unwrap())
+        .unwrap();
+    for (reference, shares) in [(1, 100), (2, 300)] {
+        pipeline.process(&Message::with_body(Body::AddOrder(AddOrder {
+            stock_locate: 1,
+            tracking_number: 0,
+            timestamp: Timestamp(34_200_000_000_001),
+            reference,
+            side: Side::Buy,
+            shares,
+            stock: Stock::from("AAPL"),
+            price: Price4(1_000_000),
+            attribution: None,
+        })));
+    }
+
+    let snapshot = Snapshot::new(&pipeline, 1, 10);
+    assert_eq!(snapshot.trading_state, Some(TradingState::Halted));
+    assert_eq!(snapshot.timestamp, Timestamp(34_200_000_000_000));
+    assert_eq!(
+        snapshot.bids,
+        [PriceLevel {
+            price: Price4(1_000_000),
+            shares: 400,
+            orders: 2
+        }]
+    );
+    assert!(snapshot.asks.is_empty());
+
+    let json: serde_json::Value =
+        serde_json::from_str(&Report::Snapshot(snapshot).to_json()).unwrap();
+    assert_eq!(json["type"], "snapshot");
+    assert_eq!(json["symbol"], "AAPL");
+    assert_eq!(json["trading_state"], "Halted");
+    assert_eq!(json["state"]["trading_reason"], "LUDP");
+    assert_eq!(json["bids"][0]["price"], "100.0000");
+}
+
+#[test]
+fn state_keeps_latest_retail_interest() {
+    let mut pipeline = Pipeline::new();
+    for (timestamp, interest) in [
+        (34_200_000_000_000, RetailInterest::Buy),
+        (34_200_000_000_500, RetailInterest::Both),
+    ] {
+        pipeline.process(&Message::with_body(Body::RetailPriceImprovement(
+            RetailPriceImprovement {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: Timestamp(timestamp),
+                stock: Stock::from("AAPL"),
+                interest,
+            },
+        )));
+    }
+
+    let rpi = pipeline
+        .state_manager
+        .state(1)
+        .unwrap()
+        .retail_interest
+        .unwrap();
+    assert_eq!(rpi.interest, RetailInterest::Both);
+    assert_eq!(rpi.since, Timestamp(34_200_000_000_500));
+    let json: serde_json::Value =
+        serde_json::from_str(&Report::Snapshot(Snapshot::new(&pipeline, 1, 10)).to_json()).unwrap();
+    assert_eq!(
+        json["state"]["retail_interest"],
+        serde_json::json!({"interest": "Both", "since": 34_200_000_000_500u64})
+    );
+}
+
+#[test]
+fn participants_quote_only_within_book_depth() {
+    let mut pipeline = Pipeline::new();
+    for (mpid, market_maker_mode, market_participant_state) in [
+        (
+            "GSCO",
+            MarketMakerMode::Normal,
+            MarketParticipantState::Active,
+        ),
+        (
+            "MSCO",
+            MarketMakerMode::Normal,
+            MarketParticipantState::Withdrawn,
+        ),
+        (
+            "JPMS",
+            MarketMakerMode::Passive,
+            MarketParticipantState::Active,
+        ),
+        (
+            "UBSS",
+            MarketMakerMode::Normal,
+            MarketParticipantState::Active,
+        ),
+    ] {
+        pipeline.process(&Message::with_body(Body::MarketParticipantPosition(
+            MarketParticipantPosition {
+                stock_locate: 1,
+                tracking_number: 0,
+                timestamp: Timestamp(34_200_000_000_000),
+                mpid: Mpid::from(mpid),
+                stock: Stock::from("AAPL"),
+                primary_market_maker: false,
+                market_maker_mode,
+                market_participant_state,
+            },
+        )));
+    }
+    // The best bid is anonymous, UBSS quotes the third level.
+    for (reference, price, attribution) in [
+        (1, 1_000_000, None),
+        (2, 999_900, Some("MSCO")),
+        (3, 999_900, Some("GSCO")),
+        (4, 999_900, Some("JPMS")),
+        (5, 999_800, Some("UBSS")),
+    ] {
+        pipeline.process(&Message::with_body(Body::AddOrder(AddOrder {
+            stock_locate: 1,
+            tracking_number: 0,
+            timestamp: Timestamp(34_200_000_000_001),
+            reference,
+            side: Side::Buy,
+            shares: 100,
+            stock: Stock::from("AAPL"),
+            price: Price4(price),
+            attribution: attribution.map(Mpid::from),
+        })));
+    }
+
+    let participants = Participants::new(&pipeline, 1, 2);
+    assert_eq!(participants.positions.len(), 4);
+    let bids: Vec<(Price4, Vec<Mpid>)> = participants
+        .bids
+        .into_iter()
+        .map(|quoting| (quoting.price, quoting.mpids))
+        .collect();
+    assert_eq!(bids, [(Price4(999_900), vec![Mpid::from("GSCO")])]);
+    assert!(participants.asks.is_empty());
+}
+
+#[test]
+fn bbo_changes_follow_top_of_book() {
+    let mut pipeline = Pipeline::new();
+    pipeline.book_manager.track_bbo();
+    let mut bbos = HashMap::new();
+    for bytes in feed() {
+        let message = Message::new(&bytes).unwrap();
+        pipeline.process(&message);
+        let changes = pipeline.book_manager.bbo_changes();
+
+        let stock_locate = MessageView::new(&bytes).unwrap().stock_locate();
+        let top = |side| {
+            pipeline
+                .book_manager
+                .levels(stock_locate, side, 1)
+                .first()
+                .map_or((None, 0), |level| (Some(level.price), level.shares))
+        };
+        let (bid_price, bid_shares) = top(Side::Buy);
+        let (ask_price, ask_shares) = top(Side::Sell);
+        let bbo = Bbo {
+            bid_price,
+            bid_shares,
+            ask_price,
+            ask_shares,
+        };
+        let previous = bbos.insert(stock_locate, bbo).unwrap_or_default();
+        if bbo == previous {
+            assert!(changes.is_empty(), "{:?}", message);
+        } else {
+            assert_eq!(changes, vec![(stock_locate, bbo)], "{:?}", message);
+        }
+    }
+}
+
+#[test]
+fn level_deltas_rebuild_books() {
+    let mut pipeline = Pipeline::new();
+    pipeline.book_manager.track_deltas();
+    let mut books: HashMap<(u16, u8), BTreeMap<Price4, Level>> = HashMap::new();
+    for (sequence, bytes) in feed().iter().enumerate() {
+        pipeline.sequence_number = sequence as u64;
+        pipeline
+            .process_view(&MessageView::new(bytes).unwrap())
+            .unwrap();
+        for (stock_locate, delta) in pipeline.book_manager.take_deltas() {
+            let update = LevelUpdate::new(&pipeline, stock_locate, delta);
+            let mut encoded = Vec::new();
+            update.encode(&mut encoded);
+            assert_eq!(encoded.len(), LevelUpdate::LENGTH);
+            assert_eq!(LevelUpdate::decode(&encoded), Ok(update));
+
+            let book = books
+                .entry((stock_locate, delta.side.to_byte()))
+                .or_default();
+            let level = Level {
+                shares: delta.shares,
+                orders: delta.orders,
+            };
+            let previous = match delta.action {
+                LevelAction::Delete => book.remove(&delta.price),
+                _ => book.insert(delta.price, level),
+            };
+            match delta.action {
+                LevelAction::New => assert_eq!(previous, None, "{:?}", update),
+                LevelAction::Change => assert!(previous.is_some(), "{:?}", update),
+                LevelAction::Delete => {
+                    assert!(previous.is_some(), "{:?}", update);
+                    assert_eq!(level, Level::default(), "{:?}", update);
+                }
+            }
+        }
+    }
+
+    for (stock_locate, book) in pipeline.book_manager.books_per_stock_locate.iter() {
+        for (side, levels) in [(Side::Buy, &book.0), (Side::Sell, &book.1)] {
+            assert_eq!(
+                books
+                    .remove(&(*stock_locate, side.to_byte()))
+                    .unwrap_or_default(),
+                *levels
+            );
+        }
+    }
+    assert!(books.values().all(BTreeMap::is_empty));
+}
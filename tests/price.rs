@@ -0,0 +1,97 @@
+use proptest::prelude::*;
+
+use bookbuilder::messages::{Body, Message};
+use bookbuilder::price::{Price4, Price8};
+
+proptest! {
+    #[test]
+    fn price4_format_parse_roundtrip(raw in any::<u32>()) {
+        let price = Price4(raw);
+        prop_assert_eq!(price.to_string().parse::<Price4>(), Ok(price));
+    }
+
+    #[test]
+    fn price8_format_parse_roundtrip(raw in any::<u64>()) {
+        let price = Price8(raw);
+        prop_assert_eq!(price.to_string().parse::<Price8>(), Ok(price));
+    }
+
+    #[test]
+    fn ordering_follows_raw_value(a in any::<u32>(), b in any::<u32>()) {
+        prop_assert_eq!(Price4(a).cmp(&Price4(b)), a.cmp(&b));
+        prop_assert_eq!(Price8::from(Price4(a)).cmp(&Price8::from(Price4(b))), a.cmp(&b));
+    }
+}
+
+#[test]
+fn price_format_is_exact() {
+    assert_eq!(Price4(1_234_500).to_string(), "123.4500");
+    assert_eq!(Price4(1).to_string(), "0.0001");
+    assert_eq!(Price4(u32::MAX).to_string(), "429496.7295");
+    assert_eq!(Price8(1).to_string(), "0.00000001");
+    assert_eq!(Price8(u64::MAX).to_string(), "184467440737.09551615");
+    assert_eq!(Price8::from(Price4(3)).to_string(), "0.00030000");
+}
+
+#[test]
+fn price_parse() {
+    assert_eq!("123.45".parse(), Ok(Price4(1_234_500)));
+    assert_eq!("7".parse(), Ok(Price4(70_000)));
+    assert_eq!("0.1".parse(), Ok(Price8(10_000_000)));
+    for invalid in [
+        "",
+        ".5",
+        "1.",
+        "-1.0",
+        "+1.0",
+        "1.00001",
+        "1e3",
+        "1.2.3",
+        "429496.7296",
+    ] {
+        assert!(invalid.parse::<Price4>().is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn price_arithmetic() {
+    let bid = Price4(1_000_000);
+    let ask = Price4(1_000_100);
+    assert_eq!(ask - bid, Price4(100));
+    assert_eq!(bid + Price4(100), ask);
+    assert_eq!(Price4(100) * 3, Price4(300));
+    assert_eq!(bid.abs_diff(ask), Price4(100));
+    assert_eq!(bid.checked_sub(ask), None);
+    assert_eq!(Price4(u32::MAX).checked_add(Price4(1)), None);
+    assert_eq!(ask.to_f64(), 100.01);
+}
+
+#[test]
+fn mwcb_levels_keep_eight_decimals() {
+    let mut bytes = vec![0, 35, b'V', 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+    for level in [4_123_456_789_012u64, 3_812_345_678_901, 3_500_000_000_001] {
+        bytes.extend_from_slice(&level.to_be_bytes());
+    }
+    let Body::MwcbDeclineLevel(decline) = Message::new(&bytes).unwrap().body else {
+        panic!("not a MWCB decline level message");
+    };
+    assert_eq!(decline.level1.to_string(), "41234.56789012");
+    assert_eq!(decline.level2.to_string(), "38123.45678901");
+    assert_eq!(decline.level3.to_string(), "35000.00000001");
+    assert_eq!(
+        Message::with_body(Body::MwcbDeclineLevel(decline)).encode(),
+        bytes
+    );
+}
+
+#[test]
+fn prices_serialize_exactly() {
+    assert_eq!(
+        serde_json::to_string(&Price8(4_123_456_789_012)).unwrap(),
+        "\"41234.56789012\""
+    );
+    assert_eq!(
+        serde_json::to_string(&Price4(1_000_100)).unwrap(),
+        "\"100.0100\""
+    );
+}
@@ -0,0 +1,226 @@
+use std::fs;
+use std::path::Path;
+
+use bookbuilder::generator::{Generator, GeneratorConfig};
+use bookbuilder::messages::Message;
+use bookbuilder::reader::{open_libpcap, MmapReader, Reader};
+use bookbuilder::timestamp::TradeDate;
+use bookbuilder::writer::{write_pcap, MAX_PAYLOAD};
+
+type Packets = Vec<(u64, u16, Option<u64>, Vec<u8>)>;
+
+fn packets(reader: &mut dyn Reader) -> Packets {
+    let mut packets = Vec::new();
+    while let Some(packet) = reader.next_packet() {
+        packets.push((
+            packet.sequence_number,
+            packet.message_count,
+            packet.timestamp,
+            packet.data.to_vec(),
+        ));
+    }
+    packets
+}
+
+fn le_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes(bytes[..4].try_into().unwrap())
+}
+
+// Frames of a little-endian microsecond pcap capture with their capture time
+// in nanoseconds.
+fn pcap_frames(capture: &[u8]) -> Vec<(u64, &[u8])> {
+    let mut frames = Vec::new();
+    let mut offset = 24;
+    while offset < capture.len() {
+        let timestamp = le_u32(&capture[offset..]) as u64 * 1_000_000_000
+            + le_u32(&capture[offset + 4..]) as u64 * 1_000;
+        let length = le_u32(&capture[offset + 8..]);
+        frames.push((
+            timestamp,
+            &capture[offset + 16..offset + 16 + length as usize],
+        ));
+        offset += 16 + length as usize;
+    }
+    frames
+}
+
+fn block(block_type: u32, body: &[u8], big_endian: bool) -> Vec<u8> {
+    let to_bytes = |value: u32| {
+        if big_endian {
+            value.to_be_bytes()
+        } else {
+            value.to_le_bytes()
+        }
+    };
+    let padding = (4 - body.len() % 4) % 4;
+    let length = (12 + body.len() + padding) as u32;
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&to_bytes(block_type));
+    bytes.extend_from_slice(&to_bytes(length));
+    bytes.extend_from_slice(body);
+    bytes.resize(bytes.len() + padding, 0);
+    bytes.extend_from_slice(&to_bytes(length));
+    bytes
+}
+
+// One pcapng section per byte order, with the frames split between them and
+// alternating between a nanosecond interface and a microsecond interface
+// offset by one hour.
+fn pcapng(frames: &[(u64, &[u8])]) -> Vec<u8> {
+    let mut capture = Vec::new();
+    let (first, second) = frames.split_at(frames.len() / 2);
+    for (big_endian, frames) in [(false, first), (true, second)] {
+        let to_bytes = |value: u32| {
+            if big_endian {
+                value.to_be_bytes()
+            } else {
+                value.to_le_bytes()
+            }
+        };
+        let option = |code: u16, value: &[u8]| {
+            let mut bytes = Vec::new();
+            let (code, length) = if big_endian {
+                (code.to_be_bytes(), (value.len() as u16).to_be_bytes())
+            } else {
+                (code.to_le_bytes(), (value.len() as u16).to_le_bytes())
+            };
+            bytes.extend_from_slice(&code);
+            bytes.extend_from_slice(&length);
+            bytes.extend_from_slice(value);
+            bytes.resize(bytes.len().div_ceil(4) * 4, 0);
+            bytes
+        };
+        let mut section = to_bytes(0x1a2b3c4d).to_vec();
+        section.extend_from_slice(&if big_endian {
+            [0, 1, 0, 0]
+        } else {
+            [1, 0, 0, 0]
+        });
+        section.extend_from_slice(&[0xff; 8]);
+        capture.extend(block(0x0a0d0d0a, &section, big_endian));
+
+        let mut interface = vec![1, 0, 0, 0, 0, 0, 0, 0];
+        if big_endian {
+            interface.swap(0, 1);
+        }
+        let mut nanosecond = interface.clone();
+        nanosecond.extend(option(9, &[9]));
+        nanosecond.extend(option(0, &[]));
+        capture.extend(block(1, &nanosecond, big_endian));
+        let mut offset = interface;
+        let hour = if big_endian {
+            3600i64.to_be_bytes()
+        } else {
+            3600i64.to_le_bytes()
+        };
+        offset.extend(option(14, &hour));
+        offset.extend(option(0, &[]));
+        capture.extend(block(1, &offset, big_endian));
+
+        for (index, (timestamp, frame)) in frames.iter().enumerate() {
+            let interface = index as u32 % 2;
+            let units = if interface == 0 {
+                *timestamp
+            } else {
+                (timestamp - 3_600_000_000_000) / 1_000
+            };
+            let mut body = Vec::new();
+            body.extend_from_slice(&to_bytes(interface));
+            body.extend_from_slice(&to_bytes((units >> 32) as u32));
+            body.extend_from_slice(&to_bytes(units as u32));
+            body.extend_from_slice(&to_bytes(frame.len() as u32));
+            body.extend_from_slice(&to_bytes(frame.len() as u32));
+            body.extend_from_slice(frame);
+            capture.extend(block(6, &body, big_endian));
+        }
+    }
+    capture
+}
+
+#[test]
+fn mapped_reader_matches_libpcap_reader() {
+    for file in ["two_symbols.pcap", "two_symbols.itch"] {
+        let path = Path::new("tests/data").join(file);
+        let expected = packets(open_libpcap(&path).unwrap().as_mut());
+        assert!(!expected.is_empty());
+        assert_eq!(
+            packets(&mut MmapReader::new(&path).unwrap()),
+            expected,
+            "{}",
+            file
+        );
+    }
+}
+
+#[test]
+fn mapped_reader_walks_pcapng_sections() {
+    let path = Path::new("tests/data/two_symbols.pcap");
+    let capture = fs::read(path).unwrap();
+    let expected = packets(open_libpcap(path).unwrap().as_mut());
+    let pcapng = pcapng(&pcap_frames(&capture));
+    assert_eq!(packets(&mut MmapReader::from_bytes(pcapng)), expected);
+}
+
+#[test]
+fn mapped_reader_stops_at_truncated_record() {
+    let path = Path::new("tests/data/two_symbols.pcap");
+    let capture = fs::read(path).unwrap();
+    let expected = packets(open_libpcap(path).unwrap().as_mut());
+    let truncated = &capture[..capture.len() - 1];
+    assert_eq!(
+        packets(&mut MmapReader::from_bytes(truncated)),
+        expected[..expected.len() - 1]
+    );
+}
+
+#[test]
+fn nanosecond_pcap_timestamps() {
+    let path = Path::new("tests/data/two_symbols.pcap");
+    let mut capture = fs::read(path).unwrap();
+    let expected = packets(open_libpcap(path).unwrap().as_mut());
+    capture[..4].copy_from_slice(&[0x4d, 0x3c, 0xb2, 0xa1]);
+    let mut offset = 24;
+    while offset < capture.len() {
+        let microseconds = le_u32(&capture[offset + 4..]);
+        capture[offset + 4..offset + 8].copy_from_slice(&(microseconds * 1_000 + 7).to_le_bytes());
+        offset += 16 + le_u32(&capture[offset + 8..]) as usize;
+    }
+
+    let packets = packets(&mut MmapReader::from_bytes(capture));
+    assert_eq!(packets.len(), expected.len());
+    for (packet, expected) in packets.iter().zip(expected) {
+        assert_eq!(packet.2, expected.2.map(|timestamp| timestamp + 7));
+    }
+}
+
+#[test]
+fn written_packets_fit_udp_and_carry_trade_date() {
+    let messages: Vec<Message> = Generator::new(GeneratorConfig {
+        message_count: 5000,
+        ..GeneratorConfig::default()
+    })
+    .collect();
+    let trade_date = TradeDate::new(2024, 2, 29).unwrap();
+    let capture = write_pcap(Vec::new(), messages.clone(), u16::MAX as usize, trade_date).unwrap();
+
+    // Every frame fits the snapshot length, so its UDP payload fits a datagram.
+    for (_, frame) in pcap_frames(&capture) {
+        assert!(frame.len() <= 65535);
+    }
+    let packets = packets(&mut MmapReader::from_bytes(capture));
+    assert!(packets.len() > 1);
+    let mut sequence_number = 1;
+    for packet in packets.iter() {
+        assert_eq!(packet.0, sequence_number);
+        assert!(packet.3.len() < MAX_PAYLOAD);
+        sequence_number += packet.1 as u64;
+    }
+    assert_eq!(sequence_number - 1, messages.len() as u64);
+
+    // 2024-02-29 is 19782 days after the epoch and five hours behind UTC.
+    let first = messages[0].body.timestamp().0;
+    assert_eq!(
+        packets[0].2,
+        Some((19_782 * 86_400 + 5 * 3_600) * 1_000_000_000 + first / 1_000 * 1_000)
+    );
+}
@@ -0,0 +1,348 @@
+use std::collections::{BTreeMap, HashMap};
+
+use proptest::prelude::*;
+
+use bookbuilder::bookmanager::{BookManager, Level, Order, OrderManager};
+use bookbuilder::enums::Side;
+use bookbuilder::messages::*;
+use bookbuilder::price::Price4;
+use bookbuilder::timestamp::Timestamp;
+
+// Obviously correct book: every live order by reference, levels are derived
+// by summing the live orders on demand.
+#[derive(Debug, Default)]
+struct ReferenceBook {
+    orders: BTreeMap<u64, (u16, Side, Price4, u32)>,
+    next_reference: u64,
+}
+
+impl ReferenceBook {
+    fn levels(&self, stock_locate: u16, side: Side) -> BTreeMap<Price4, Level> {
+        let mut levels = BTreeMap::new();
+        for (locate, order_side, price, shares) in self.orders.values() {
+            if *locate == stock_locate && *order_side == side {
+                let level: &mut Level = levels.entry(*price).or_default();
+                level.shares += shares;
+                level.orders += 1;
+            }
+        }
+        levels
+    }
+
+    fn pick(&self, selector: usize) -> Option<(u64, (u16, Side, Price4, u32))> {
+        if self.orders.is_empty() {
+            return None;
+        }
+        self.orders
+            .iter()
+            .nth(selector % self.orders.len())
+            .map(|(reference, order)| (*reference, *order))
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Event {
+    Add {
+        stock_locate: u16,
+        side: Side,
+        price: Price4,
+        shares: u32,
+    },
+    Execute {
+        selector: usize,
+        shares: u32,
+        full: bool,
+    },
+    ExecuteWithPrice {
+        selector: usize,
+        shares: u32,
+        full: bool,
+    },
+    Cancel {
+        selector: usize,
+        shares: u32,
+        full: bool,
+    },
+    Delete {
+        selector: usize,
+    },
+    Replace {
+        selector: usize,
+        price: Price4,
+        shares: u32,
+    },
+}
+
+fn event() -> impl Strategy<Value = Event> {
+    let price = (1..20u32).prop_map(|tick| Price4(tick * 100));
+    let shares = 1..500u32;
+    prop_oneof![
+        3 => (1..=2u16, any::<bool>(), price.clone(), shares.clone()).prop_map(
+            |(stock_locate, buy, price, shares)| Event::Add {
+                stock_locate,
+                side: if buy { Side::Buy } else { Side::Sell },
+                price,
+                shares,
+            }
+        ),
+        1 => (any::<usize>(), shares.clone(), any::<bool>())
+            .prop_map(|(selector, shares, full)| Event::Execute { selector, shares, full }),
+        1 => (any::<usize>(), shares.clone(), any::<bool>()).prop_map(
+            |(selector, shares, full)| Event::ExecuteWithPrice { selector, shares, full }
+        ),
+        1 => (any::<usize>(), shares.clone(), any::<bool>())
+            .prop_map(|(selector, shares, full)| Event::Cancel { selector, shares, full }),
+        1 => any::<usize>().prop_map(|selector| Event::Delete { selector }),
+        1 => (any::<usize>(), price, shares)
+            .prop_map(|(selector, price, shares)| Event::Replace { selector, price, shares }),
+    ]
+}
+
+fn reduced_shares(remaining: u32, shares: u32, full: bool) -> u32 {
+    if full {
+        remaining
+    } else {
+        1 + shares % remaining
+    }
+}
+
+// Applies the event to the reference book and returns the equivalent valid
+// ITCH message, or None when the event targets an order and none is live.
+fn apply(model: &mut ReferenceBook, event: &Event) -> Option<Message> {
+    let body = match *event {
+        Event::Add {
+            stock_locate,
+            side,
+            price,
+            shares,
+        } => {
+            model.next_reference += 1;
+            let reference = model.next_reference;
+            model
+                .orders
+                .insert(reference, (stock_locate, side, price, shares));
+            Body::AddOrder(AddOrder {
+                stock_locate,
+                tracking_number: 0,
+                timestamp: Timestamp(0),
+                reference,
+                side,
+                shares,
+                stock: Stock::from("TEST"),
+                price,
+                attribution: None,
+            })
+        }
+        Event::Execute {
+            selector,
+            shares,
+            full,
+        } => {
+            let (reference, (stock_locate, _, _, remaining)) = model.pick(selector)?;
+            let executed_shares = reduced_shares(remaining, shares, full);
+            reduce(model, reference, executed_shares);
+            Body::ExecutedOrder(ExecutedOrder {
+                stock_locate,
+                tracking_number: 0,
+                timestamp: Timestamp(0),
+                reference,
+                executed_shares,
+                match_number: 0,
+            })
+        }
+        Event::ExecuteWithPrice {
+            selector,
+            shares,
+            full,
+        } => {
+            let (reference, (stock_locate, _, price, remaining)) = model.pick(selector)?;
+            let executed_shares = reduced_shares(remaining, shares, full);
+            reduce(model, reference, executed_shares);
+            Body::ExecutedWithPriceOrder(ExecutedWithPriceOrder {
+                stock_locate,
+                tracking_number: 0,
+                timestamp: Timestamp(0),
+                reference,
+                executed_shares,
+                match_number: 0,
+                printable: true,
+                price: price + Price4(100),
+            })
+        }
+        Event::Cancel {
+            selector,
+            shares,
+            full,
+        } => {
+            let (reference, (stock_locate, _, _, remaining)) = model.pick(selector)?;
+            let canceled_shares = reduced_shares(remaining, shares, full);
+            reduce(model, reference, canceled_shares);
+            Body::CancelOrder(CancelOrder {
+                stock_locate,
+                tracking_number: 0,
+                timestamp: Timestamp(0),
+                reference,
+                canceled_shares,
+            })
+        }
+        Event::Delete { selector } => {
+            let (reference, (stock_locate, _, _, _)) = model.pick(selector)?;
+            model.orders.remove(&reference);
+            Body::DeleteOrder(DeleteOrder {
+                stock_locate,
+                tracking_number: 0,
+                timestamp: Timestamp(0),
+                reference,
+            })
+        }
+        Event::Replace {
+            selector,
+            price,
+            shares,
+        } => {
+            let (original_reference, (stock_locate, side, _, _)) = model.pick(selector)?;
+            model.orders.remove(&original_reference);
+            model.next_reference += 1;
+            let new_reference = model.next_reference;
+            model
+                .orders
+                .insert(new_reference, (stock_locate, side, price, shares));
+            Body::ReplaceOrder(ReplaceOrder {
+                stock_locate,
+                tracking_number: 0,
+                timestamp: Timestamp(0),
+                original_reference,
+                new_reference,
+                shares,
+                price,
+            })
+        }
+    };
+    Some(Message::with_body(body))
+}
+
+fn reduce(model: &mut ReferenceBook, reference: u64, shares: u32) {
+    let order = model.orders.get_mut(&reference).unwrap();
+    order.3 -= shares;
+    if order.3 == 0 {
+        model.orders.remove(&reference);
+    }
+}
+
+fn book_levels(
+    book_manager: &BookManager,
+    stock_locate: u16,
+    side: Side,
+) -> BTreeMap<Price4, Level> {
+    book_manager
+        .books_per_stock_locate
+        .get(&stock_locate)
+        .map(|book| match side {
+            Side::Buy => book.0.clone(),
+            Side::Sell => book.1.clone(),
+        })
+        .unwrap_or_default()
+}
+
+fn check(model: &ReferenceBook, order_manager: &OrderManager, book_manager: &BookManager) {
+    for stock_locate in 1..=2 {
+        for side in [Side::Buy, Side::Sell] {
+            assert_eq!(
+                book_levels(book_manager, stock_locate, side),
+                model.levels(stock_locate, side),
+                "locate {} {:?} levels",
+                stock_locate,
+                side
+            );
+        }
+    }
+    for (reference, (stock_locate, _, price, _)) in &model.orders {
+        assert_eq!(
+            order_manager.order_price(*stock_locate, *reference),
+            Some(*price),
+            "order {}",
+            reference
+        );
+    }
+}
+
+fn run(events: &[Event]) {
+    let mut model = ReferenceBook::default();
+    let mut listing = HashMap::new();
+    let mut order_manager = OrderManager::new();
+    let mut book_manager = BookManager::new();
+
+    for event in events {
+        if let Some(message) = apply(&mut model, event) {
+            let order = Order::new(&message, &mut listing);
+            order_manager.process(&order, &mut book_manager);
+            check(&model, &order_manager, &book_manager);
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    #[test]
+    fn book_matches_reference_model(events in prop::collection::vec(event(), 1..200)) {
+        run(&events);
+    }
+}
+
+#[test]
+fn full_execution_removes_level() {
+    run(&[
+        Event::Add {
+            stock_locate: 1,
+            side: Side::Buy,
+            price: Price4(100),
+            shares: 300,
+        },
+        Event::Execute {
+            selector: 0,
+            shares: 0,
+            full: true,
+        },
+    ]);
+}
+
+#[test]
+fn full_cancel_removes_level() {
+    run(&[
+        Event::Add {
+            stock_locate: 1,
+            side: Side::Sell,
+            price: Price4(100),
+            shares: 300,
+        },
+        Event::Cancel {
+            selector: 0,
+            shares: 0,
+            full: true,
+        },
+    ]);
+}
+
+#[test]
+fn replaced_order_is_tracked_by_new_reference() {
+    run(&[
+        Event::Add {
+            stock_locate: 1,
+            side: Side::Buy,
+            price: Price4(100),
+            shares: 300,
+        },
+        Event::Replace {
+            selector: 0,
+            price: Price4(200),
+            shares: 100,
+        },
+        Event::Execute {
+            selector: 0,
+            shares: 50,
+            full: false,
+        },
+        Event::Delete { selector: 0 },
+    ]);
+}
@@ -0,0 +1,707 @@
+use proptest::prelude::*;
+use proptest::sample::select;
+
+use bookbuilder::enums::*;
+use bookbuilder::error::DecodeError;
+use bookbuilder::messages::*;
+use bookbuilder::price::{Price4, Price8};
+use bookbuilder::timestamp::Timestamp;
+use bookbuilder::utils::{as_u16, PACKET_HEADER_LENGTH};
+
+fn header() -> impl Strategy<Value = (u16, u16, Timestamp)> {
+    (
+        any::<u16>(),
+        any::<u16>(),
+        (0..1u64 << 48).prop_map(Timestamp),
+    )
+}
+
+fn alpha<const N: usize>() -> impl Strategy<Value = Alpha<N>> {
+    proptest::string::string_regex(&format!("[A-Z]{{1,{}}}", N))
+        .unwrap()
+        .prop_map(|value| Alpha::from(value.as_str()))
+}
+
+fn byte_enum<T: std::fmt::Debug + 'static>(
+    bytes: &'static [u8],
+    new: fn(u8) -> Result<T, DecodeError>,
+) -> BoxedStrategy<T> {
+    select(bytes)
+        .prop_map(move |byte| new(byte).unwrap())
+        .boxed()
+}
+
+fn add_order() -> impl Strategy<Value = Body> {
+    (
+        header(),
+        any::<u64>(),
+        byte_enum(b"BS", Side::new),
+        any::<u32>(),
+        alpha::<8>(),
+        any::<u32>(),
+        proptest::option::of(alpha::<4>()),
+    )
+        .prop_map(
+            |(
+                (stock_locate, tracking_number, timestamp),
+                reference,
+                side,
+                shares,
+                stock,
+                price,
+                attribution,
+            )| {
+                Body::AddOrder(AddOrder {
+                    stock_locate,
+                    tracking_number,
+                    timestamp,
+                    reference,
+                    side,
+                    shares,
+                    stock,
+                    price: Price4(price),
+                    attribution,
+                })
+            },
+        )
+}
+
+fn executed_order() -> impl Strategy<Value = Body> {
+    (header(), any::<u64>(), any::<u32>(), any::<u64>()).prop_map(
+        |((stock_locate, tracking_number, timestamp), reference, executed_shares, match_number)| {
+            Body::ExecutedOrder(ExecutedOrder {
+                stock_locate,
+                tracking_number,
+                timestamp,
+                reference,
+                executed_shares,
+                match_number,
+            })
+        },
+    )
+}
+
+fn executed_with_price_order() -> impl Strategy<Value = Body> {
+    (
+        header(),
+        any::<u64>(),
+        any::<u32>(),
+        any::<u64>(),
+        any::<bool>(),
+        any::<u32>(),
+    )
+        .prop_map(
+            |(
+                (stock_locate, tracking_number, timestamp),
+                reference,
+                executed_shares,
+                match_number,
+                printable,
+                price,
+            )| {
+                Body::ExecutedWithPriceOrder(ExecutedWithPriceOrder {
+                    stock_locate,
+                    tracking_number,
+                    timestamp,
+                    reference,
+                    executed_shares,
+                    match_number,
+                    printable,
+                    price: Price4(price),
+                })
+            },
+        )
+}
+
+fn cancel_order() -> impl Strategy<Value = Body> {
+    (header(), any::<u64>(), any::<u32>()).prop_map(
+        |((stock_locate, tracking_number, timestamp), reference, canceled_shares)| {
+            Body::CancelOrder(CancelOrder {
+                stock_locate,
+                tracking_number,
+                timestamp,
+                reference,
+                canceled_shares,
+            })
+        },
+    )
+}
+
+fn delete_order() -> impl Strategy<Value = Body> {
+    (header(), any::<u64>()).prop_map(|((stock_locate, tracking_number, timestamp), reference)| {
+        Body::DeleteOrder(DeleteOrder {
+            stock_locate,
+            tracking_number,
+            timestamp,
+            reference,
+        })
+    })
+}
+
+fn replace_order() -> impl Strategy<Value = Body> {
+    (
+        header(),
+        any::<u64>(),
+        any::<u64>(),
+        any::<u32>(),
+        any::<u32>(),
+    )
+        .prop_map(
+            |(
+                (stock_locate, tracking_number, timestamp),
+                original_reference,
+                new_reference,
+                price,
+                shares,
+            )| {
+                Body::ReplaceOrder(ReplaceOrder {
+                    stock_locate,
+                    tracking_number,
+                    timestamp,
+                    original_reference,
+                    new_reference,
+                    price: Price4(price),
+                    shares,
+                })
+            },
+        )
+}
+
+fn stock_directory() -> impl Strategy<Value = Body> {
+    let issue_subtypes: Vec<&'static [u8]> = vec![
+        b"A ", b"AI", b"B ", b"C ", b"CB", b"CF", b"CL", b"CM", b"CO", b"CT", b"CU", b"CW", b"D ",
+        b"E ", b"EG", b"EI", b"EM", b"EN", b"EU", b"F ", b"FI", b"FL", b"G ", b"I ", b"IR", b"IW",
+        b"IX", b"J ", b"L ", b"LL", b"M ", b"MF", b"ML", b"MT", b"N ", b"O ", b"P ", b"PP", b"PU",
+        b"Q ", b"R ", b"RC", b"RF", b"RT", b"RU", b"S ", b"SC", b"SI", b"T ", b"TC", b"TU", b"U ",
+        b"V ", b"W ", b"WC", b"X ", b"Y ", b"Z ",
+    ];
+    (
+        (
+            header(),
+            alpha::<8>(),
+            byte_enum(b"QGSNAPZV ", MarketCategory::new),
+            byte_enum(b"DEQSGHJKCN ", FinancialStatus::new),
+            any::<u32>(),
+            any::<bool>(),
+            byte_enum(b"ABCFILNOPQRSTUVW", IssueClassification::new),
+            select(issue_subtypes).prop_map(|bytes| IssueSubType::new(bytes).unwrap()),
+        ),
+        (
+            byte_enum(b"PT", Authenticity::new),
+            byte_enum(b"YN ", ThresholdIndicator::new),
+            byte_enum(b"YN ", IpoFlag::new),
+            byte_enum(b"12 ", LuldRefPriceTier::new),
+            byte_enum(b"YN ", EtpFlag::new),
+            any::<u32>(),
+            any::<bool>(),
+        ),
+    )
+        .prop_map(
+            |(
+                (
+                    (stock_locate, tracking_number, timestamp),
+                    stock,
+                    market_category,
+                    financial_status,
+                    round_lot_size,
+                    round_lots_only,
+                    issue_classification,
+                    issue_subtype,
+                ),
+                (
+                    authenticity,
+                    short_sale_threshold,
+                    ipo_flag,
+                    luld_ref_price_tier,
+                    etp_flag,
+                    etp_leverage_factor,
+                    inverse_indicator,
+                ),
+            )| {
+                Body::StockDirectory(StockDirectory {
+                    stock_locate,
+                    tracking_number,
+                    timestamp,
+                    stock,
+                    market_category,
+                    financial_status,
+                    round_lot_size,
+                    round_lots_only,
+                    issue_classification,
+                    issue_subtype,
+                    authenticity,
+                    short_sale_threshold,
+                    ipo_flag,
+                    luld_ref_price_tier,
+                    etp_flag,
+                    etp_leverage_factor,
+                    inverse_indicator,
+                })
+            },
+        )
+}
+
+fn stock_trading_action() -> impl Strategy<Value = Body> {
+    (
+        header(),
+        alpha::<8>(),
+        byte_enum(b"HPQT", TradingState::new),
+        alpha::<4>(),
+    )
+        .prop_map(
+            |((stock_locate, tracking_number, timestamp), stock, trading_state, reason)| {
+                Body::StockTradingAction(StockTradingAction {
+                    stock_locate,
+                    tracking_number,
+                    timestamp,
+                    stock,
+                    trading_state,
+                    reason,
+                })
+            },
+        )
+}
+
+fn reg_sho_restriction() -> impl Strategy<Value = Body> {
+    (header(), alpha::<8>(), byte_enum(b"012", RegShoAction::new)).prop_map(
+        |((stock_locate, tracking_number, timestamp), stock, reg_sho_action)| {
+            Body::RegShoRestriction(RegShoRestriction {
+                stock_locate,
+                tracking_number,
+                timestamp,
+                stock,
+                reg_sho_action,
+            })
+        },
+    )
+}
+
+fn market_participant_position() -> impl Strategy<Value = Body> {
+    (
+        header(),
+        alpha::<4>(),
+        alpha::<8>(),
+        any::<bool>(),
+        byte_enum(b"NPSRL", MarketMakerMode::new),
+        byte_enum(b"AEWSD", MarketParticipantState::new),
+    )
+        .prop_map(
+            |(
+                (stock_locate, tracking_number, timestamp),
+                mpid,
+                stock,
+                primary_market_maker,
+                market_maker_mode,
+                market_participant_state,
+            )| {
+                Body::MarketParticipantPosition(MarketParticipantPosition {
+                    stock_locate,
+                    tracking_number,
+                    timestamp,
+                    mpid,
+                    stock,
+                    primary_market_maker,
+                    market_maker_mode,
+                    market_participant_state,
+                })
+            },
+        )
+}
+
+fn mwcb_decline_level() -> impl Strategy<Value = Body> {
+    (header(), any::<u64>(), any::<u64>(), any::<u64>()).prop_map(
+        |((stock_locate, tracking_number, timestamp), level1, level2, level3)| {
+            Body::MwcbDeclineLevel(MwcbDeclineLevel {
+                stock_locate,
+                tracking_number,
+                timestamp,
+                level1: Price8(level1),
+                level2: Price8(level2),
+                level3: Price8(level3),
+            })
+        },
+    )
+}
+
+fn mwcb_status() -> impl Strategy<Value = Body> {
+    (header(), byte_enum(b"123", MwcbLevel::new)).prop_map(
+        |((stock_locate, tracking_number, timestamp), breached_level)| {
+            Body::MwcbStatus(MwcbStatus {
+                stock_locate,
+                tracking_number,
+                timestamp,
+                breached_level,
+            })
+        },
+    )
+}
+
+fn ipo_quoting_period_update() -> impl Strategy<Value = Body> {
+    (
+        header(),
+        alpha::<8>(),
+        0..86_400u64,
+        byte_enum(b"AC", IpoReleaseQualifier::new),
+        any::<u32>(),
+    )
+        .prop_map(
+            |(
+                (stock_locate, tracking_number, timestamp),
+                stock,
+                release_time,
+                release_qualifier,
+                ipo_price,
+            )| {
+                Body::IpoQuotingPeriodUpdate(IpoQuotingPeriodUpdate {
+                    stock_locate,
+                    tracking_number,
+                    timestamp,
+                    stock,
+                    release_time: Timestamp::from_seconds(release_time),
+                    release_qualifier,
+                    ipo_price: Price4(ipo_price),
+                })
+            },
+        )
+}
+
+fn luld_auction_collar() -> impl Strategy<Value = Body> {
+    (
+        header(),
+        alpha::<8>(),
+        any::<u32>(),
+        any::<u32>(),
+        any::<u32>(),
+        any::<u32>(),
+    )
+        .prop_map(
+            |(
+                (stock_locate, tracking_number, timestamp),
+                stock,
+                reference_price,
+                upper_price,
+                lower_price,
+                extension,
+            )| {
+                Body::LuldAuctionCollar(LuldAuctionCollar {
+                    stock_locate,
+                    tracking_number,
+                    timestamp,
+                    stock,
+                    reference_price: Price4(reference_price),
+                    upper_price: Price4(upper_price),
+                    lower_price: Price4(lower_price),
+                    extension,
+                })
+            },
+        )
+}
+
+fn operational_halt() -> impl Strategy<Value = Body> {
+    (
+        header(),
+        alpha::<8>(),
+        byte_enum(b"QBX", MarketCode::new),
+        byte_enum(b"HT", OperationalHaltAction::new),
+    )
+        .prop_map(
+            |((stock_locate, tracking_number, timestamp), stock, market_code, action)| {
+                Body::OperationalHalt(OperationalHalt {
+                    stock_locate,
+                    tracking_number,
+                    timestamp,
+                    stock,
+                    market_code,
+                    action,
+                })
+            },
+        )
+}
+
+fn direct_listing_price_discovery() -> impl Strategy<Value = Body> {
+    (
+        header(),
+        alpha::<8>(),
+        any::<bool>(),
+        any::<u32>(),
+        any::<u32>(),
+        any::<u32>(),
+        any::<u64>(),
+        any::<u32>(),
+        any::<u32>(),
+    )
+        .prop_map(
+            |(
+                (stock_locate, tracking_number, timestamp),
+                stock,
+                open_eligible,
+                min_allowable_price,
+                max_allowable_price,
+                near_execution_price,
+                near_execution_time,
+                lower_price_range_collar,
+                upper_price_range_collar,
+            )| {
+                Body::DirectListingPriceDiscovery(DirectListingPriceDiscovery {
+                    stock_locate,
+                    tracking_number,
+                    timestamp,
+                    stock,
+                    open_eligible,
+                    min_allowable_price: Price4(min_allowable_price),
+                    max_allowable_price: Price4(max_allowable_price),
+                    near_execution_price: Price4(near_execution_price),
+                    near_execution_time: Timestamp(near_execution_time),
+                    lower_price_range_collar: Price4(lower_price_range_collar),
+                    upper_price_range_collar: Price4(upper_price_range_collar),
+                })
+            },
+        )
+}
+
+fn non_cross_trade() -> impl Strategy<Value = Body> {
+    (
+        header(),
+        any::<u64>(),
+        byte_enum(b"BS", Side::new),
+        any::<u32>(),
+        alpha::<8>(),
+        any::<u32>(),
+        any::<u64>(),
+    )
+        .prop_map(
+            |(
+                (stock_locate, tracking_number, timestamp),
+                reference,
+                side,
+                shares,
+                stock,
+                price,
+                match_number,
+            )| {
+                Body::NonCrossTrade(NonCrossTrade {
+                    stock_locate,
+                    tracking_number,
+                    timestamp,
+                    reference,
+                    side,
+                    shares,
+                    stock,
+                    price: Price4(price),
+                    match_number,
+                })
+            },
+        )
+}
+
+fn cross_trade() -> impl Strategy<Value = Body> {
+    (
+        header(),
+        any::<u64>(),
+        alpha::<8>(),
+        any::<u32>(),
+        any::<u64>(),
+        byte_enum(b"OCHIA", CrossType::new),
+    )
+        .prop_map(
+            |(
+                (stock_locate, tracking_number, timestamp),
+                shares,
+                stock,
+                cross_price,
+                match_number,
+                cross_type,
+            )| {
+                Body::CrossTrade(CrossTrade {
+                    stock_locate,
+                    tracking_number,
+                    timestamp,
+                    shares,
+                    stock,
+                    cross_price: Price4(cross_price),
+                    match_number,
+                    cross_type,
+                })
+            },
+        )
+}
+
+fn broken_trade() -> impl Strategy<Value = Body> {
+    (header(), any::<u64>()).prop_map(
+        |((stock_locate, tracking_number, timestamp), match_number)| {
+            Body::BrokenTrade(BrokenTrade {
+                stock_locate,
+                tracking_number,
+                timestamp,
+                match_number,
+            })
+        },
+    )
+}
+
+fn net_order_imbalance() -> impl Strategy<Value = Body> {
+    (
+        header(),
+        any::<u64>(),
+        any::<u64>(),
+        byte_enum(b"BSNOP", ImbalanceDirection::new),
+        alpha::<8>(),
+        any::<u32>(),
+        any::<u32>(),
+        any::<u32>(),
+        byte_enum(b"OCHA", CrossType::new),
+        byte_enum(b"L123456789ABC ", PriceVariationIndicator::new),
+    )
+        .prop_map(
+            |(
+                (stock_locate, tracking_number, timestamp),
+                paired_shares,
+                imbalance_shares,
+                imbalance_direction,
+                stock,
+                far_price,
+                near_price,
+                current_reference_price,
+                cross_type,
+                price_variation_indicator,
+            )| {
+                Body::NetOrderImbalance(NetOrderImbalance {
+                    stock_locate,
+                    tracking_number,
+                    timestamp,
+                    paired_shares,
+                    imbalance_shares,
+                    imbalance_direction,
+                    stock,
+                    far_price: Price4(far_price),
+                    near_price: Price4(near_price),
+                    current_reference_price: Price4(current_reference_price),
+                    cross_type,
+                    price_variation_indicator,
+                })
+            },
+        )
+}
+
+fn retail_price_improvement() -> impl Strategy<Value = Body> {
+    (
+        header(),
+        alpha::<8>(),
+        byte_enum(b"BSAN", RetailInterest::new),
+    )
+        .prop_map(
+            |((stock_locate, tracking_number, timestamp), stock, interest)| {
+                Body::RetailPriceImprovement(RetailPriceImprovement {
+                    stock_locate,
+                    tracking_number,
+                    timestamp,
+                    stock,
+                    interest,
+                })
+            },
+        )
+}
+
+fn system_event() -> impl Strategy<Value = Body> {
+    (header(), byte_enum(b"OSQMEC", SystemEventCode::new)).prop_map(
+        |((stock_locate, tracking_number, timestamp), event_code)| {
+            Body::SystemEvent(SystemEvent {
+                stock_locate,
+                tracking_number,
+                timestamp,
+                event_code,
+            })
+        },
+    )
+}
+
+// Types outside the specification carry at least the common header.
+fn unknown() -> impl Strategy<Value = Body> {
+    (
+        select(b"GMTZaz".as_slice()),
+        proptest::collection::vec(any::<u8>(), 10..40),
+    )
+        .prop_map(|(msg_type, bytes)| Body::Unknown { msg_type, bytes })
+}
+
+fn body() -> impl Strategy<Value = Body> {
+    prop_oneof![
+        add_order(),
+        executed_order(),
+        executed_with_price_order(),
+        cancel_order(),
+        delete_order(),
+        replace_order(),
+        stock_directory(),
+        stock_trading_action(),
+        reg_sho_restriction(),
+        market_participant_position(),
+        mwcb_decline_level(),
+        mwcb_status(),
+        ipo_quoting_period_update(),
+        luld_auction_collar(),
+        operational_halt(),
+        direct_listing_price_discovery(),
+        non_cross_trade(),
+        cross_trade(),
+        broken_trade(),
+        net_order_imbalance(),
+        retail_price_improvement(),
+        system_event(),
+        unknown(),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn decode_encode_roundtrip(body in body()) {
+        let message = Message::with_body(body);
+        let bytes = message.encode();
+        prop_assert_eq!(as_u16(&bytes[..2]) as usize, bytes.len() - 2);
+        prop_assert_eq!(Message::new(&bytes), Ok(message.clone()));
+
+        let view = MessageView::new(&bytes).unwrap();
+        prop_assert_eq!(view.msg_type(), message.msg_type);
+        prop_assert_eq!(view.timestamp(), message.body.timestamp());
+        prop_assert_eq!(view.bytes(), &bytes[..]);
+    }
+
+    #[test]
+    fn truncated_message_is_rejected(body in body(), cut in any::<prop::sample::Index>()) {
+        let bytes = Message::with_body(body).encode();
+        let truncated = &bytes[..cut.index(bytes.len())];
+        let is_truncated = matches!(Message::new(truncated), Err(DecodeError::Truncated { .. }));
+        prop_assert!(is_truncated);
+    }
+
+    #[test]
+    fn arbitrary_bytes_do_not_panic(bytes in proptest::collection::vec(any::<u8>(), 0..64)) {
+        let _ = Message::new(&bytes);
+        let _ = PacketHeader::new(&bytes);
+    }
+
+    #[test]
+    fn packet_roundtrip(
+        bodies in proptest::collection::vec(body(), 0..20),
+        sequence_number in any::<u64>(),
+    ) {
+        let messages: Vec<Message> = bodies.into_iter().map(Message::with_body).collect();
+        let mut builder = PacketBuilder::new("SESSION001", sequence_number);
+        for message in messages.iter() {
+            builder.add(message);
+        }
+        let packet = builder.build();
+
+        let header = PacketHeader::new(&packet).unwrap();
+        prop_assert_eq!(header.sequence_number, sequence_number);
+        prop_assert_eq!(header.message_count as usize, messages.len());
+
+        let mut offset = PACKET_HEADER_LENGTH;
+        for message in messages.iter() {
+            let decoded = Message::new(&packet[offset..]).unwrap();
+            offset += decoded.length as usize + 2;
+            prop_assert_eq!(&decoded, message);
+        }
+        prop_assert_eq!(offset, packet.len());
+    }
+}
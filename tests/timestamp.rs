@@ -0,0 +1,123 @@
+use std::path::Path;
+
+use proptest::prelude::*;
+
+use bookbuilder::generator::{Generator, GeneratorConfig};
+use bookbuilder::messages::MessageView;
+use bookbuilder::timestamp::{Timestamp, TradeDate, UtcTime};
+
+fn date(value: &str) -> TradeDate {
+    value.parse().unwrap()
+}
+
+proptest! {
+    #[test]
+    fn format_parse_roundtrip(nanoseconds in 0..86_400_000_000_000u64) {
+        let timestamp = Timestamp(nanoseconds);
+        prop_assert_eq!(timestamp.to_string().parse::<Timestamp>(), Ok(timestamp));
+    }
+}
+
+#[test]
+fn timestamp_format_and_parse() {
+    let timestamp = Timestamp(34_200_000_086_097);
+    assert_eq!(timestamp.to_string(), "09:30:00.000086097");
+    assert_eq!("09:30:00.000086097".parse(), Ok(timestamp));
+    assert_eq!("09:30:00".parse(), Ok(Timestamp(34_200_000_000_000)));
+    assert_eq!("16:00:00.5".parse(), Ok(Timestamp(57_600_500_000_000)));
+    for invalid in [
+        "",
+        "09:30",
+        "9:30:00.",
+        "09:60:00",
+        "24:00:00",
+        "09:30:00.0000000001",
+        "09:30:0a",
+        "-1:30:00",
+    ] {
+        assert!(invalid.parse::<Timestamp>().is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn trade_date_parse() {
+    assert_eq!(date("2019-01-30"), TradeDate::new(2019, 1, 30).unwrap());
+    assert_eq!(date("20190130"), TradeDate::new(2019, 1, 30).unwrap());
+    assert_eq!(date("2024-02-29").to_string(), "2024-02-29");
+    for invalid in [
+        "2023-02-29",
+        "2019-13-01",
+        "2019/01/30",
+        "201901300",
+        "01-30-2019",
+    ] {
+        assert!(invalid.parse::<TradeDate>().is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn trade_date_from_file_name() {
+    for (path, expected) in [
+        ("/data/01302019.NASDAQ_ITCH50", "2019-01-30"),
+        ("S20190130-v50.txt", "2019-01-30"),
+        ("capture_2023-11-21.pcapng", "2023-11-21"),
+        ("12292023.pcap", "2023-12-29"),
+    ] {
+        assert_eq!(
+            TradeDate::from_path(Path::new(path)),
+            Some(date(expected)),
+            "{}",
+            path
+        );
+    }
+    assert_eq!(TradeDate::from_path(Path::new("nasdaq_capture.pcap")), None);
+}
+
+#[test]
+fn utc_follows_eastern_daylight_saving() {
+    let open = Timestamp(34_200_000_000_000);
+    for (trade_date, expected) in [
+        ("2023-03-10", "2023-03-10T14:30:00.000000000Z"),
+        ("2023-03-12", "2023-03-12T14:30:00.000000000Z"),
+        ("2023-03-13", "2023-03-13T13:30:00.000000000Z"),
+        ("2023-11-03", "2023-11-03T13:30:00.000000000Z"),
+        ("2023-11-05", "2023-11-05T13:30:00.000000000Z"),
+        ("2023-11-06", "2023-11-06T14:30:00.000000000Z"),
+        ("2006-04-03", "2006-04-03T13:30:00.000000000Z"),
+        ("2006-10-30", "2006-10-30T14:30:00.000000000Z"),
+        ("2024-02-29", "2024-02-29T14:30:00.000000000Z"),
+    ] {
+        assert_eq!(
+            open.to_utc(date(trade_date)).to_string(),
+            expected,
+            "{}",
+            trade_date
+        );
+    }
+    assert_eq!(
+        open.to_utc(date("2023-11-21")),
+        UtcTime::from_epoch("1700577000").unwrap()
+    );
+}
+
+#[test]
+fn epoch_parse() {
+    assert_eq!(
+        UtcTime::from_epoch("1700577000.000000001"),
+        Ok(UtcTime(1_700_577_000_000_000_001))
+    );
+    assert_eq!(UtcTime::from_epoch("1.5"), Ok(UtcTime(1_500_000_000)));
+    for invalid in ["", ".5", "1.", "1.0000000001", "18446744074"] {
+        assert!(UtcTime::from_epoch(invalid).is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn messages_expose_their_timestamp() {
+    for message in Generator::new(GeneratorConfig::default()).take(500) {
+        let bytes = message.encode();
+        let view = MessageView::new(&bytes).unwrap();
+        assert_eq!(view.timestamp(), message.body.timestamp());
+        assert!(message.body.timestamp() >= GeneratorConfig::default().start_time);
+    }
+}